@@ -0,0 +1,62 @@
+//! How the core attributes turn into everything else. An attribute of 10 is
+//! average; every two points above or below it is worth a point of bonus or
+//! penalty.
+
+/// Armour class of a creature wearing nothing at all, before quickness.
+pub const BASE_ARMOR_CLASS: i32 = 10;
+/// Extra maximum hit points for every point of fitness bonus.
+const HP_PER_FITNESS: i32 = 3;
+/// How much weight each point of might lets a creature carry.
+const CARRY_PER_MIGHT: i32 = 3;
+
+pub fn attr_bonus(value: i32) -> i32 {
+    (value - 10).div_euclid(2)
+}
+
+/// Maximum hit points for a creature with `base_hp` before fitness is counted.
+pub fn max_hp(base_hp: i32, fitness: i32) -> i32 {
+    i32::max(1, base_hp + fitness_hp(fitness))
+}
+
+/// The part of a creature's maximum hit points that comes from fitness.
+pub fn fitness_hp(fitness: i32) -> i32 {
+    attr_bonus(fitness) * HP_PER_FITNESS
+}
+
+pub fn carry_capacity(might: i32) -> i32 {
+    might * CARRY_PER_MIGHT
+}
+
+/// Creatures with a worse initiative than the player now and then lose a
+/// turn while it acts.
+pub fn initiative(quickness: i32) -> i32 {
+    attr_bonus(quickness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bonus_grows_every_two_points() {
+        assert_eq!(attr_bonus(10), 0);
+        assert_eq!(attr_bonus(11), 0);
+        assert_eq!(attr_bonus(12), 1);
+        assert_eq!(attr_bonus(18), 4);
+    }
+
+    #[test]
+    fn penalties_round_down() {
+        assert_eq!(attr_bonus(9), -1);
+        assert_eq!(attr_bonus(8), -1);
+        assert_eq!(attr_bonus(7), -2);
+        assert_eq!(attr_bonus(0), -5);
+        assert_eq!(attr_bonus(-1), -6);
+    }
+
+    #[test]
+    fn max_hp_never_drops_below_one() {
+        assert_eq!(max_hp(5, 3), 1);
+        assert_eq!(max_hp(30, 14), 36);
+    }
+}
@@ -12,6 +12,14 @@ pub struct Position {
     pub y: i32,
 }
 
+/// Where an entity waits while the player is on another level.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct OtherLevelPosition {
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Renderable {
     pub glyph: rltk::FontCharType,
@@ -38,6 +46,25 @@ pub struct Name {
     pub name: String,
 }
 
+/// A one-of-a-kind monster, with its own last words.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Unique {
+    pub death_message: String,
+}
+
+/// Follows `leader` around, and joins the fight as soon as the leader spots
+/// the player.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct PackMember {
+    pub leader: Entity,
+}
+
+/// Names the raws loot table rolled when this entity dies.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct LootTable {
+    pub table: String,
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
 
@@ -49,13 +76,48 @@ pub struct CombatStats {
     pub power: i32,
 }
 
+/// The core attributes; see `attributes` for what they're good for.
 #[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Attributes {
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+}
+
+/// Damage waiting to be applied this turn, with its type and whoever dealt
+/// it. It never outlives the turn, so it isn't saved.
+#[derive(Component, Debug, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>,
+    pub amount: Vec<(i32, DamageType, Option<Entity>)>,
+}
+
+/// Left on a creature by the blow that killed it, so the killer can be
+/// rewarded.
+#[derive(Component, Debug, Clone)]
+pub struct KilledBy {
+    pub killer: Entity,
+}
+
+/// How far a creature has come. `unspent_level_ups` are levels the player
+/// hasn't picked a bonus for yet.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+    pub unspent_level_ups: i32,
+}
+
+/// The experience a monster is worth to whoever kills it.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct ExperienceValue {
+    pub xp: i32,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct Item {}
+pub struct Item {
+    pub weight: i32,
+}
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Consumable {}
@@ -68,6 +130,7 @@ pub struct Ranged {
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -94,6 +157,7 @@ pub struct InBackpack {
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Ranged,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -117,6 +181,152 @@ pub struct DefensePowerBonus {
     pub defense: i32,
 }
 
+/// A weapon's damage roll: 1d8+2 is one eight-sided die plus two.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct MeleeWeapon {
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+}
+
+/// A bow or crossbow: how far it shoots, and the damage roll of each shot.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+}
+
+/// Balanced for throwing: the damage roll when it hits somebody.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct ThrownWeapon {
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+}
+
+/// Breaks when thrown, splashing its effect over everyone within `radius`.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct Shatters {
+    pub radius: i32,
+}
+
+/// A stack of arrows. Stacks with the same name merge when picked up.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct Ammunition {
+    pub count: i32,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Cold,
+    Poison,
+    Magic,
+}
+
+impl std::fmt::Display for DamageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            DamageType::Physical => "physical",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+            DamageType::Magic => "magic",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Damage types that only do half as much to this creature.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Resistances {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Damage types that don't hurt this creature at all.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Immunities {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Damage types that do double to this creature.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Vulnerabilities {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Extra damage a weapon deals on every blow, on top of the wielder's power,
+/// one entry per damage type. Armour doesn't help against it.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct BonusDamage {
+    pub damage: Vec<(DamageType, i32)>,
+}
+
+/// Heals the wielder by a percentage of the damage they deal.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct LifeSteal {
+    pub percent: i32,
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Rarity {
+    Common,
+    Magic,
+    Rare,
+}
+
+/// Gear that was generated with affixes. The affixes' bonuses are already
+/// folded into its other components; this just remembers how rare it is.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct MagicItem {
+    pub rarity: Rarity,
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Blessing {
+    Blessed,
+    Cursed,
+}
+
+/// Gear that has been blessed or cursed; anything without one is uncursed.
+/// `known` is set once the player has found out which.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Beatitude {
+    pub blessing: Blessing,
+    pub known: bool,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Hidden {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct EntryTrigger {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SingleActivation {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Teleports {}
+
+/// Reveals what everything in the user's backpack really is.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct IdentifiesItems {}
+
+/// Lifts the curses on everything the user is wearing or carrying.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RemovesCurse {}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct RaisesAlarm {
+    pub radius: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Alerted {}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ParticleLifetime {
     pub lifetime_ms: f32,
@@ -127,4 +337,6 @@ pub struct SerializeMe;
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: super::map::Map,
+    pub dungeon: super::map::MasterDungeonMap,
+    pub seed: u64,
 }
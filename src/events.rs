@@ -10,6 +10,11 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToShoot {
+    pub target: rltk::Point,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToPickupItem {
     pub collected_by: Entity,
@@ -20,6 +25,15 @@ pub struct WantsToPickupItem {
 pub struct WantsToUseItem {
     pub item: Entity,
     pub target: Option<rltk::Point>,
+    /// The item was thrown and breaks where it lands, rather than being
+    /// used by hand. Nobody learns what it was from the splash.
+    pub thrown: bool,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToThrowItem {
+    pub item: Entity,
+    pub target: rltk::Point,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -31,3 +45,6 @@ pub struct WantsToDropItem {
 pub struct WantsToRemoveItem {
     pub item: Entity,
 }
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct EntityMoved {}
@@ -4,6 +4,7 @@ use rltk::{GameState, Point, Rltk};
 use specs::prelude::*;
 use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
 
+mod attributes;
 mod components;
 use components::*;
 mod map;
@@ -14,7 +15,8 @@ mod systems;
 use systems::*;
 mod events;
 use events::*;
-use ui::{gamelog, gui};
+use ui::{camera, gamelog, gui};
+mod raws;
 mod ui;
 
 #[derive(PartialEq, Copy, Clone)]
@@ -25,16 +27,27 @@ pub enum RunState {
     MonsterTurn,
     ShowInventory,
     ShowDropItem,
+    ShowThrowItem,
     ShowRemoveItem,
+    ShowCharacterSheet,
+    LevelUp,
     ShowTargeting {
         range: i32,
         item: Entity,
     },
+    ShowRangedTargeting {
+        range: i32,
+    },
+    ShowThrowTargeting {
+        item: Entity,
+    },
     MainMenu {
         menu_selection: gui::MainMenuSelection,
     },
     SaveGame,
     NextLevel,
+    PreviousLevel,
+    FallThroughChasm,
     GameOver,
 }
 
@@ -53,15 +66,27 @@ impl State {
         let mut mapindex = MapIndexingSystem {};
         mapindex.run_now(&self.ecs);
 
+        let mut triggers = TriggerSystem {};
+        triggers.run_now(&self.ecs);
+
         let mut melee = MeleeCombatSystem {};
         melee.run_now(&self.ecs);
 
+        let mut ranged = RangedCombatSystem {};
+        ranged.run_now(&self.ecs);
+
+        let mut hazards = HazardSystem {};
+        hazards.run_now(&self.ecs);
+
         let mut damage = DamageSystem {};
         damage.run_now(&self.ecs);
 
         let mut pickup = ItemCollectionSystem {};
         pickup.run_now(&self.ecs);
 
+        let mut throw_items = ItemThrowSystem {};
+        throw_items.run_now(&self.ecs);
+
         let mut itemuse = ItemUseSystem {};
         itemuse.run_now(&self.ecs);
 
@@ -91,24 +116,8 @@ impl GameState for State {
         match newrunstate {
             RunState::MainMenu { .. } => {}
             _ => {
-                draw_map(&self.ecs, ctx);
-
-                {
-                    let positions = self.ecs.read_storage::<Position>();
-                    let renderables = self.ecs.read_storage::<Renderable>();
-                    let map = self.ecs.fetch::<Map>();
-
-                    let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
-                    data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
-                    for (pos, render) in data.iter() {
-                        let idx = map.xy_idx(pos.x, pos.y);
-                        if map.visible_tiles[idx] {
-                            ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph)
-                        }
-                    }
-
-                    gui::draw_ui(&self.ecs, ctx);
-                }
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
             }
         }
 
@@ -153,6 +162,7 @@ impl GameState for State {
                                     WantsToUseItem {
                                         item: item_entity,
                                         target: None,
+                                        thrown: false,
                                     },
                                 )
                                 .expect("Unable to insert intent");
@@ -179,6 +189,18 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowThrowItem => {
+                let result = gui::throw_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        newrunstate = RunState::ShowThrowTargeting {
+                            item: result.1.unwrap(),
+                        };
+                    }
+                }
+            }
             RunState::ShowRemoveItem => {
                 let result = gui::remove_item_menu(self, ctx);
                 match result.0 {
@@ -197,6 +219,23 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowCharacterSheet => {
+                if gui::show_character_sheet(&self.ecs, ctx) == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::LevelUp => {
+                if let (gui::ItemMenuResult::Selected, Some(bonus)) =
+                    gui::level_up_menu(&self.ecs, ctx)
+                {
+                    // Level ups only come from the player's own kills, so the monsters are next
+                    newrunstate = if apply_level_up_bonus(&mut self.ecs, bonus) {
+                        RunState::LevelUp
+                    } else {
+                        RunState::MonsterTurn
+                    };
+                }
+            }
             RunState::ShowTargeting { range, item } => {
                 let result = gui::ranged_target(self, ctx, range);
                 match result.0 {
@@ -210,6 +249,46 @@ impl GameState for State {
                                 WantsToUseItem {
                                     item,
                                     target: result.1,
+                                    thrown: false,
+                                },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowRangedTargeting { range } => {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let mut intent = self.ecs.write_storage::<WantsToShoot>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToShoot {
+                                    target: result.1.unwrap(),
+                                },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowThrowTargeting { item } => {
+                let result = gui::ranged_target(self, ctx, THROW_RANGE);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let mut intent = self.ecs.write_storage::<WantsToThrowItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToThrowItem {
+                                    item,
+                                    target: result.1.unwrap(),
                                 },
                             )
                             .expect("Unable to insert intent");
@@ -226,7 +305,10 @@ impl GameState for State {
                         }
                     }
                     gui::MainMenuResult::Selected { selected } => match selected {
-                        gui::MainMenuSelection::NewGame => newrunstate = RunState::PreRun,
+                        gui::MainMenuSelection::NewGame | gui::MainMenuSelection::Seed { .. } => {
+                            self.new_game();
+                            newrunstate = RunState::PreRun;
+                        }
                         gui::MainMenuSelection::LoadGame => {
                             saveload_system::load_game(&mut self.ecs);
                             newrunstate = RunState::AwaitingInput;
@@ -245,15 +327,26 @@ impl GameState for State {
                 };
             }
             RunState::NextLevel => {
-                self.goto_next_level();
+                self.goto_level(1);
+                newrunstate = RunState::PreRun;
+            }
+            RunState::PreviousLevel => {
+                self.goto_level(-1);
+                newrunstate = RunState::PreRun;
+            }
+            RunState::FallThroughChasm => {
+                self.fall_through_chasm();
                 newrunstate = RunState::PreRun;
             }
             RunState::GameOver => {
-                let result = gui::game_over(ctx);
+                let result = gui::game_over(&self.ecs, ctx);
                 match result {
                     gui::GameOverResult::NoSelection => {}
                     gui::GameOverResult::QuitToMenu => {
-                        self.game_over_cleanup();
+                        // The next run gets a dungeon of its own, unless a seed was asked for
+                        *self.ecs.write_resource::<MasterSeed>() =
+                            MasterSeed::from_args().unwrap_or_else(MasterSeed::random);
+                        self.new_game();
                         newrunstate = RunState::MainMenu {
                             menu_selection: gui::MainMenuSelection::NewGame,
                         };
@@ -273,72 +366,111 @@ impl GameState for State {
 }
 
 impl State {
-    fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity> {
-        let entities = self.ecs.entities();
-        let player = self.ecs.read_storage::<Player>();
-        let backpack = self.ecs.read_storage::<InBackpack>();
-        let equipped = self.ecs.read_storage::<Equipped>();
-        let player_entity = self.ecs.fetch::<Entity>();
-
-        let mut to_delete: Vec<Entity> = Vec::new();
-        for entity in entities.join() {
-            let mut should_delete = true;
-
-            // Don't delete the player
-            let p = player.get(entity);
-            if let Some(_p) = p {
-                should_delete = false;
-            }
+    /// Moves the player `offset` levels down (or up, if negative). The level
+    /// being left is stored as it is, a level visited before comes back
+    /// exactly as it was left, and a new level gets built.
+    fn goto_level(&mut self, offset: i32) {
+        self.change_level(offset);
 
-            // Don't delete the player's equipment
-            let bp = backpack.get(entity);
-            if let Some(bp) = bp {
-                if bp.owner == *player_entity {
-                    should_delete = false;
-                }
-            }
-
-            let eq = equipped.get(entity);
-            if let Some(eq) = eq {
-                if eq.owner == *player_entity {
-                    should_delete = false;
-                }
+        // Notify the player and give them some health
+        let player_entity = self.ecs.fetch::<Entity>();
+        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
+        if offset < 0 {
+            gamelog
+                .entries
+                .push("You climb back up to the previous level.".to_string());
+        } else {
+            gamelog
+                .entries
+                .push("You descend to the next level, and take a moment to heal.".to_string());
+            let mut player_health_store = self.ecs.write_storage::<CombatStats>();
+            let player_health = player_health_store.get_mut(*player_entity);
+            if let Some(player_health) = player_health {
+                player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
             }
+        }
+    }
 
-            if should_delete {
-                to_delete.push(entity);
-            }
+    /// Drops the player onto a random floor tile of the next level down. The
+    /// landing hurts.
+    fn fall_through_chasm(&mut self) {
+        self.change_level(1);
+
+        let landing = {
+            let map = self.ecs.fetch::<Map>();
+            let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
+            let floor: Vec<usize> = map
+                .tiles
+                .iter()
+                .enumerate()
+                .filter(|(_idx, tile)| **tile == TileType::Floor)
+                .map(|(idx, _tile)| idx)
+                .collect();
+            rng.random_slice_entry(&floor)
+                .map(|idx| Point::new(*idx as i32 % map.width, *idx as i32 / map.width))
+        };
+        if let Some(landing) = landing {
+            self.place_player(landing);
         }
 
-        to_delete
+        let player_entity = *self.ecs.fetch::<Entity>();
+        let damage = self
+            .ecs
+            .write_resource::<rltk::RandomNumberGenerator>()
+            .roll_dice(1, 6);
+        SufferDamage::new_damage(
+            &mut self.ecs.write_storage::<SufferDamage>(),
+            player_entity,
+            damage,
+            DamageType::Physical,
+            None,
+        );
+        self.ecs
+            .fetch_mut::<gamelog::GameLog>()
+            .entries
+            .push("You fall through the chasm and land hard!".to_string());
     }
 
-    fn goto_next_level(&mut self) {
-        // Delete entities that aren't the player or his/her equipment
-        let to_delete = self.entities_to_remove_on_level_change();
-        for target in to_delete {
-            self.ecs
-                .delete_entity(target)
-                .expect("Unable to delete entity");
-        }
+    /// Leaves the current level for the one `offset` levels down (or up, if
+    /// negative), recalling it from the dungeon store if it was visited before.
+    fn change_level(&mut self, offset: i32) {
+        freeze_level_entities(&mut self.ecs);
 
-        // Build a new map and place the player
-        let worldmap;
-        let current_depth;
+        let current_depth = self.ecs.fetch::<Map>().depth;
+        let new_depth = current_depth + offset;
         {
-            let mut worldmap_resource = self.ecs.write_resource::<Map>();
-            current_depth = worldmap_resource.depth;
-            *worldmap_resource = Map::new_map_rooms_and_corridors(current_depth + 1);
-            worldmap = worldmap_resource.clone();
+            let mut dungeon = self.ecs.write_resource::<MasterDungeonMap>();
+            dungeon.store_map(&self.ecs.fetch::<Map>());
         }
 
-        // Spawn bad guys
-        for room in worldmap.rooms.iter().skip(1) {
-            spawner::spawn_room(&mut self.ecs, room, current_depth + 1);
-        }
+        // Recall or build the map, arriving by the stairs leading back where we came from
+        let stored_map = self.ecs.fetch::<MasterDungeonMap>().get_map(new_depth);
+        let player_pos = if let Some(map) = stored_map {
+            let arrival_tile = if offset < 0 {
+                TileType::DownStairs
+            } else {
+                TileType::UpStairs
+            };
+            let arrival_idx = map
+                .tiles
+                .iter()
+                .position(|tile| *tile == arrival_tile)
+                .unwrap_or(0) as i32;
+            let arrival = Point::new(arrival_idx % map.width, arrival_idx / map.width);
+            *self.ecs.write_resource::<Map>() = map;
+            thaw_level_entities(&mut self.ecs);
+            arrival
+        } else {
+            let player_start = self.generate_world_map(new_depth);
+            Point::new(player_start.x, player_start.y)
+        };
+
+        self.place_player(player_pos);
+    }
 
-        // Place the player and update resources
-        let player_pos = worldmap.rooms[0].center();
+    /// Moves the player to a spot on the current map and makes everybody on
+    /// the level take a fresh look around.
+    fn place_player(&mut self, player_pos: Point) {
         let mut player_position = self.ecs.write_resource::<Point>();
         *player_position = player_pos;
         let mut position_components = self.ecs.write_storage::<Position>();
@@ -349,26 +481,46 @@ impl State {
             player_pos_comp.y = player_pos.y;
         }
 
-        // Mark the player's visibility as dirty
         let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
-        let vs = viewshed_components.get_mut(*player_entity);
-        if let Some(vs) = vs {
+        for vs in (&mut viewshed_components).join() {
             vs.dirty = true;
         }
+    }
 
-        // Notify the player and give them some health
-        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
-        gamelog
-            .entries
-            .push("You descend to the next level, and take a moment to heal.".to_string());
-        let mut player_health_store = self.ecs.write_storage::<CombatStats>();
-        let player_health = player_health_store.get_mut(*player_entity);
-        if let Some(player_health) = player_health {
-            player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
+    /// Builds a new map for the given depth, makes it the current map and
+    /// spawns its entities. Returns where the player should start.
+    fn generate_world_map(&mut self, new_depth: i32) -> Position {
+        let mut rng = self.ecs.fetch::<MasterSeed>().rng_for_depth(new_depth);
+        // A level without a way down is no good, so keep building until there is one
+        let mut builder = loop {
+            let mut builder = builders::random_builder(new_depth, &mut rng);
+            builder.build_map(&mut rng);
+            if builder.get_map().tiles.contains(&TileType::DownStairs) {
+                break builder;
+            }
+        };
+
+        let start = builder.get_starting_position();
+        {
+            let mut worldmap_resource = self.ecs.write_resource::<Map>();
+            *worldmap_resource = builder.get_map();
+            if new_depth > 1 {
+                let up_stairs_idx = worldmap_resource.xy_idx(start.x, start.y);
+                worldmap_resource.tiles[up_stairs_idx] = TileType::UpStairs;
+            }
         }
+
+        // Spawning, and whatever happens on this level afterwards, keeps
+        // drawing from the level's generator
+        *self.ecs.write_resource::<rltk::RandomNumberGenerator>() = rng;
+        builder.spawn_entities(&mut self.ecs);
+        spawner::spawn_uniques(&mut self.ecs);
+
+        start
     }
 
-    fn game_over_cleanup(&mut self) {
+    /// Throws away the current run and sets up depth 1 for the current seed.
+    fn new_game(&mut self) {
         let mut to_delete = Vec::new();
         for e in self.ecs.entities().join() {
             to_delete.push(e);
@@ -376,22 +528,11 @@ impl State {
         for del in to_delete.iter() {
             self.ecs.delete_entity(*del).expect("Deletion faild");
         }
+        let dungeon = MasterDungeonMap::new(&self.ecs.fetch::<MasterSeed>());
+        *self.ecs.write_resource::<MasterDungeonMap>() = dungeon;
 
         // Build a new map and place the player
-        let worldmap;
-        {
-            let mut worldmap_resource = self.ecs.write_resource::<Map>();
-            *worldmap_resource = Map::new_map_rooms_and_corridors(1);
-            worldmap = worldmap_resource.clone();
-        }
-
-        // Spawn bad guys
-        for room in worldmap.rooms.iter().skip(1) {
-            spawner::spawn_room(&mut self.ecs, room, 1);
-        }
-
-        // Place the player and update resources
-        let player_start_pos = worldmap.rooms[0].center();
+        let player_start_pos = self.generate_world_map(1);
         let player_entity = spawner::player(&mut self.ecs, player_start_pos.x, player_start_pos.y);
         let mut player_position = self.ecs.write_resource::<Point>();
         *player_position = Point::new(player_start_pos.x, player_start_pos.y);
@@ -413,6 +554,70 @@ impl State {
     }
 }
 
+/// Tells the world about every kind of component the game uses.
+fn register_components(ecs: &mut World) {
+    ecs.register::<Position>();
+    ecs.register::<OtherLevelPosition>();
+    ecs.register::<Renderable>();
+    ecs.register::<Player>();
+    ecs.register::<Viewshed>();
+    ecs.register::<Monster>();
+    ecs.register::<Name>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<CombatStats>();
+    ecs.register::<Attributes>();
+    ecs.register::<WantsToMelee>();
+    ecs.register::<WantsToShoot>();
+    ecs.register::<SufferDamage>();
+    ecs.register::<KilledBy>();
+    ecs.register::<Experience>();
+    ecs.register::<ExperienceValue>();
+    ecs.register::<Item>();
+    ecs.register::<ProvidesHealing>();
+    ecs.register::<InflictsDamage>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<Consumable>();
+    ecs.register::<Ranged>();
+    ecs.register::<InBackpack>();
+    ecs.register::<WantsToPickupItem>();
+    ecs.register::<WantsToUseItem>();
+    ecs.register::<WantsToDropItem>();
+    ecs.register::<WantsToThrowItem>();
+    ecs.register::<WantsToRemoveItem>();
+    ecs.register::<Confusion>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<MeleePowerBonus>();
+    ecs.register::<DefensePowerBonus>();
+    ecs.register::<MeleeWeapon>();
+    ecs.register::<RangedWeapon>();
+    ecs.register::<ThrownWeapon>();
+    ecs.register::<Shatters>();
+    ecs.register::<Ammunition>();
+    ecs.register::<Hidden>();
+    ecs.register::<EntryTrigger>();
+    ecs.register::<SingleActivation>();
+    ecs.register::<Teleports>();
+    ecs.register::<IdentifiesItems>();
+    ecs.register::<Beatitude>();
+    ecs.register::<BonusDamage>();
+    ecs.register::<Resistances>();
+    ecs.register::<Immunities>();
+    ecs.register::<Vulnerabilities>();
+    ecs.register::<LifeSteal>();
+    ecs.register::<MagicItem>();
+    ecs.register::<RemovesCurse>();
+    ecs.register::<RaisesAlarm>();
+    ecs.register::<Alerted>();
+    ecs.register::<LootTable>();
+    ecs.register::<Unique>();
+    ecs.register::<PackMember>();
+    ecs.register::<EntityMoved>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+    ecs.register::<ParticleLifetime>();
+}
+
 fn main() -> rltk::BError {
     use rltk::RltkBuilder;
     let mut context = RltkBuilder::simple80x50()
@@ -424,50 +629,24 @@ fn main() -> rltk::BError {
 
     let mut gs = State { ecs: World::new() };
 
-    gs.ecs.register::<Position>();
-    gs.ecs.register::<Renderable>();
-    gs.ecs.register::<Player>();
-    gs.ecs.register::<Viewshed>();
-    gs.ecs.register::<Monster>();
-    gs.ecs.register::<Name>();
-    gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<CombatStats>();
-    gs.ecs.register::<WantsToMelee>();
-    gs.ecs.register::<SufferDamage>();
-    gs.ecs.register::<Item>();
-    gs.ecs.register::<ProvidesHealing>();
-    gs.ecs.register::<InflictsDamage>();
-    gs.ecs.register::<AreaOfEffect>();
-    gs.ecs.register::<Consumable>();
-    gs.ecs.register::<Ranged>();
-    gs.ecs.register::<InBackpack>();
-    gs.ecs.register::<WantsToPickupItem>();
-    gs.ecs.register::<WantsToUseItem>();
-    gs.ecs.register::<WantsToDropItem>();
-    gs.ecs.register::<WantsToRemoveItem>();
-    gs.ecs.register::<Confusion>();
-    gs.ecs.register::<Equippable>();
-    gs.ecs.register::<Equipped>();
-    gs.ecs.register::<MeleePowerBonus>();
-    gs.ecs.register::<DefensePowerBonus>();
-    gs.ecs.register::<SimpleMarker<SerializeMe>>();
-    gs.ecs.register::<SerializationHelper>();
-    gs.ecs.register::<ParticleLifetime>();
+    register_components(&mut gs.ecs);
+
+    raws::load_raws();
 
     gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
 
-    let map: Map = Map::new_map_rooms_and_corridors(1);
-    let player_start_pos = map.rooms[0].center();
+    gs.ecs
+        .insert(MasterSeed::from_args().unwrap_or_else(MasterSeed::random));
+    gs.ecs.insert(rltk::RandomNumberGenerator::new());
+    gs.ecs.insert(Map::new(1, 1, 1));
+    let dungeon = MasterDungeonMap::new(&gs.ecs.fetch::<MasterSeed>());
+    gs.ecs.insert(dungeon);
+    let player_start_pos = gs.generate_world_map(1);
 
     let player_entity = spawner::player(&mut gs.ecs, player_start_pos.x, player_start_pos.y);
 
-    gs.ecs.insert(rltk::RandomNumberGenerator::new());
-    for room in map.rooms.iter().skip(1) {
-        spawner::spawn_room(&mut gs.ecs, room, 1);
-    }
-
-    gs.ecs.insert(map);
-    gs.ecs.insert(player_start_pos);
+    gs.ecs
+        .insert(Point::new(player_start_pos.x, player_start_pos.y));
     gs.ecs.insert(player_entity);
     gs.ecs.insert(RunState::MainMenu {
         menu_selection: gui::MainMenuSelection::NewGame,
@@ -479,3 +658,48 @@ fn main() -> rltk::BError {
 
     rltk::main_loop(context, gs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds `depth` of a fresh dungeon for `seed` and describes every item
+    /// lying around on it.
+    fn items_on_level(seed: u64, depth: i32) -> Vec<String> {
+        let mut gs = State { ecs: World::new() };
+        register_components(&mut gs.ecs);
+        raws::load_raws();
+        gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        gs.ecs.insert(MasterSeed::new(seed));
+        gs.ecs.insert(rltk::RandomNumberGenerator::new());
+        gs.ecs.insert(Map::new(1, 1, 1));
+        let dungeon = MasterDungeonMap::new(&gs.ecs.fetch::<MasterSeed>());
+        gs.ecs.insert(dungeon);
+        gs.generate_world_map(depth);
+
+        let names = gs.ecs.read_storage::<Name>();
+        let items = gs.ecs.read_storage::<Item>();
+        let positions = gs.ecs.read_storage::<Position>();
+        let beatitudes = gs.ecs.read_storage::<Beatitude>();
+        (&names, &items, &positions, beatitudes.maybe())
+            .join()
+            .map(|(name, _item, pos, beatitude)| {
+                let blessing = match beatitude.map(|b| b.blessing) {
+                    Some(Blessing::Blessed) => " (blessed)",
+                    Some(Blessing::Cursed) => " (cursed)",
+                    _ => "",
+                };
+                format!("{}{} at {},{}", name.name, blessing, pos.x, pos.y)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_spawns_the_same_items() {
+        let first = items_on_level(1234, 4);
+        let second = items_on_level(1234, 4);
+
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,111 @@
+use super::{
+    common::{generate_voronoi_spawn_regions, nearest_floor_tile, remove_unreachable_areas},
+    spawner, Map, MapBuilder,
+};
+use crate::{map::TileType, Position};
+use rltk::RandomNumberGenerator;
+
+/// Natural looking caves: random noise smoothed out by a few rounds of
+/// cellular automata.
+pub struct CellularAutomataBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+
+        let noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
+        let areas: Vec<Vec<usize>> = noise_areas.into_values().collect();
+        spawner::spawn_level(
+            &self.map,
+            rng,
+            &areas,
+            &self.starting_position,
+            self.depth,
+            &mut self.spawn_list,
+        );
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl CellularAutomataBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> CellularAutomataBuilder {
+        CellularAutomataBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        // First we completely randomize the map, setting 55% of it to be floor.
+        for y in 1..self.map.height - 1 {
+            for x in 1..self.map.width - 1 {
+                let roll = rng.roll_dice(1, 100);
+                let idx = self.map.xy_idx(x, y);
+                if roll > 55 {
+                    self.map.tiles[idx] = TileType::Floor
+                } else {
+                    self.map.tiles[idx] = TileType::Wall
+                }
+            }
+        }
+
+        // Now we iteratively apply cellular automata rules
+        for _i in 0..15 {
+            let mut newtiles = self.map.tiles.clone();
+
+            for y in 1..self.map.height - 1 {
+                for x in 1..self.map.width - 1 {
+                    let idx = self.map.xy_idx(x, y);
+                    let w = self.map.width as usize;
+                    let neighbors = [
+                        idx - 1,
+                        idx + 1,
+                        idx - w,
+                        idx + w,
+                        idx - (w - 1),
+                        idx - (w + 1),
+                        idx + (w - 1),
+                        idx + (w + 1),
+                    ]
+                    .iter()
+                    .filter(|n| self.map.tiles[**n] == TileType::Wall)
+                    .count();
+
+                    if neighbors > 4 || neighbors == 0 {
+                        newtiles[idx] = TileType::Wall;
+                    } else {
+                        newtiles[idx] = TileType::Floor;
+                    }
+                }
+            }
+
+            self.map.tiles = newtiles;
+        }
+
+        // Start on the floor tile closest to the middle of the map
+        let (x, y) = nearest_floor_tile(&self.map, self.map.width / 2, self.map.height / 2);
+        self.starting_position = Position { x, y };
+
+        // Remove what we can't reach, so nothing spawns in sealed-off pockets
+        let start_idx = self.map.xy_idx(x, y);
+        remove_unreachable_areas(&mut self.map, start_idx);
+    }
+}
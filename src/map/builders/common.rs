@@ -0,0 +1,148 @@
+use super::Map;
+use crate::map::TileType;
+use rltk::{RandomNumberGenerator, Rect};
+use std::{
+    cmp::{max, min},
+    collections::BTreeMap,
+};
+
+pub fn apply_room_to_map(map: &mut Map, room: &Rect) {
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) {
+    for x in min(x1, x2)..=max(x1, x2) {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
+    for y in min(y1, y2)..=max(y1, y2) {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+/// Puts a closed door wherever a corridor enters one of the rooms through a
+/// one tile wide gap in the room's wall.
+pub fn place_doors(map: &mut Map, rooms: &[Rect]) {
+    for room in rooms.iter() {
+        // The ring of wall around the room's floor
+        for x in room.x1..=room.x2 + 1 {
+            try_door(map, x, room.y1, true);
+            try_door(map, x, room.y2 + 1, true);
+        }
+        for y in room.y1 + 1..=room.y2 {
+            try_door(map, room.x1, y, false);
+            try_door(map, room.x2 + 1, y, false);
+        }
+    }
+}
+
+fn try_door(map: &mut Map, x: i32, y: i32, horizontal_wall: bool) {
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+        return;
+    }
+    let idx = map.xy_idx(x, y);
+    if map.tiles[idx] != TileType::Floor {
+        return;
+    }
+
+    let (side_a, side_b) = if horizontal_wall {
+        (map.xy_idx(x - 1, y), map.xy_idx(x + 1, y))
+    } else {
+        (map.xy_idx(x, y - 1), map.xy_idx(x, y + 1))
+    };
+    let next_to_door = [
+        idx - 1,
+        idx + 1,
+        idx - map.width as usize,
+        idx + map.width as usize,
+    ]
+    .iter()
+    .any(|n| map.tiles[*n] == TileType::ClosedDoor);
+    if map.tiles[side_a] == TileType::Wall && map.tiles[side_b] == TileType::Wall && !next_to_door {
+        map.tiles[idx] = TileType::ClosedDoor;
+    }
+}
+
+/// Turns every walkable tile the start can't reach back into wall; hazards are
+/// left alone since pathfinding never enters them anyway. Returns the Dijkstra
+/// distance of every tile from the start, `f32::MAX` where there is no path.
+pub fn remove_unreachable_areas(map: &mut Map, start_idx: usize) -> Vec<f32> {
+    map.populate_blocked();
+    let map_starts: Vec<usize> = vec![start_idx];
+    let dijkstra_map = rltk::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &map_starts,
+        map,
+        (map.width * map.height) as f32,
+    );
+
+    for (i, tile) in map.tiles.iter_mut().enumerate() {
+        if *tile != TileType::Wall && !tile.is_hazard() && dijkstra_map.map[i] == f32::MAX {
+            *tile = TileType::Wall;
+        }
+    }
+    map.populate_blocked();
+
+    dijkstra_map.map
+}
+
+/// Splits the floor of a map into regions using cellular noise, so that
+/// maps without rooms still have areas to populate.
+pub fn generate_voronoi_spawn_regions(
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+) -> BTreeMap<i32, Vec<usize>> {
+    let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+    let mut noise = rltk::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
+    noise.set_noise_type(rltk::NoiseType::Cellular);
+    noise.set_frequency(0.08);
+    noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
+
+    for y in 1..map.height - 1 {
+        for x in 1..map.width - 1 {
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::Floor {
+                let cell_value_f = noise.get_noise(x as f32, y as f32) * 10240.0;
+                let cell_value = cell_value_f as i32;
+
+                noise_areas.entry(cell_value).or_default().push(idx);
+            }
+        }
+    }
+
+    noise_areas
+}
+
+/// Returns the floor tile closest to the given point, searching outwards.
+pub fn nearest_floor_tile(map: &Map, x: i32, y: i32) -> (i32, i32) {
+    let mut best = (x, y);
+    let mut best_distance = f32::MAX;
+    for ty in 1..map.height - 1 {
+        for tx in 1..map.width - 1 {
+            let idx = map.xy_idx(tx, ty);
+            if map.tiles[idx] == TileType::Floor {
+                let distance = rltk::DistanceAlg::PythagorasSquared
+                    .distance2d(rltk::Point::new(x, y), rltk::Point::new(tx, ty));
+                if distance < best_distance {
+                    best_distance = distance;
+                    best = (tx, ty);
+                }
+            }
+        }
+    }
+    best
+}
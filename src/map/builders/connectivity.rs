@@ -0,0 +1,85 @@
+use super::{common::remove_unreachable_areas, Map, MapBuilder};
+use crate::{map::TileType, Position};
+use rltk::RandomNumberGenerator;
+
+/// Wraps another builder and makes sure the level it built can actually be
+/// played: everything is reachable from the start and the exit is as far
+/// away from it as the map allows.
+pub struct ConnectivityBuilder {
+    map: Map,
+    starting_position: Position,
+    previous_builder: Box<dyn MapBuilder>,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for ConnectivityBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.previous_builder.build_map(rng);
+        self.map = self.previous_builder.get_map();
+        self.starting_position = self.previous_builder.get_starting_position();
+        self.spawn_list = self.previous_builder.get_spawn_list().clone();
+
+        if !validate_connectivity(&mut self.map, &self.starting_position, &mut self.spawn_list) {
+            rltk::console::log("WARNING: Nowhere to put the exit, the level will be rebuilt");
+        }
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl ConnectivityBuilder {
+    pub fn new(new_depth: i32, previous_builder: Box<dyn MapBuilder>) -> ConnectivityBuilder {
+        ConnectivityBuilder {
+            map: Map::new(new_depth, 0, 0),
+            starting_position: Position { x: 0, y: 0 },
+            previous_builder,
+            spawn_list: Vec::new(),
+        }
+    }
+}
+
+/// Flood fills the map from the starting position. Floor the player can't
+/// reach is walled up, spawns that ended up in it are dropped, and the down
+/// stairs are moved to the reachable floor tile farthest from the start.
+/// Returns false, leaving the map without a way down, if nothing but the
+/// start itself can be reached.
+pub fn validate_connectivity(
+    map: &mut Map,
+    starting_position: &Position,
+    spawn_list: &mut Vec<(usize, String)>,
+) -> bool {
+    let start_idx = map.xy_idx(starting_position.x, starting_position.y);
+
+    // Whatever stairs the generator placed, we pick our own
+    for tile in map.tiles.iter_mut() {
+        if *tile == TileType::DownStairs {
+            *tile = TileType::Floor;
+        }
+    }
+
+    let distances = remove_unreachable_areas(map, start_idx);
+
+    spawn_list.retain(|(idx, _name)| distances[*idx] != f32::MAX);
+
+    let mut exit_tile = (start_idx, 0.0f32);
+    for (i, tile) in map.tiles.iter().enumerate() {
+        if *tile == TileType::Floor && distances[i] != f32::MAX && distances[i] > exit_tile.1 {
+            exit_tile = (i, distances[i]);
+        }
+    }
+    if exit_tile.0 == start_idx {
+        return false;
+    }
+    map.tiles[exit_tile.0] = TileType::DownStairs;
+    true
+}
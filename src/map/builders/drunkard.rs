@@ -0,0 +1,138 @@
+use super::{
+    common::{generate_voronoi_spawn_regions, remove_unreachable_areas},
+    spawner, Map, MapBuilder,
+};
+use crate::{map::TileType, Position};
+use rltk::RandomNumberGenerator;
+
+/// Sends "drunken" diggers staggering through solid rock until enough of the
+/// map has been dug out.
+pub struct DrunkardsWalkBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+
+        let noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
+        let areas: Vec<Vec<usize>> = noise_areas.into_values().collect();
+        spawner::spawn_level(
+            &self.map,
+            rng,
+            &areas,
+            &self.starting_position,
+            self.depth,
+            &mut self.spawn_list,
+        );
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl DrunkardsWalkBuilder {
+    const DRUNKEN_LIFETIME: i32 = 400;
+    const FLOOR_PERCENT: f32 = 0.5;
+
+    pub fn new(new_depth: i32, width: i32, height: i32) -> DrunkardsWalkBuilder {
+        DrunkardsWalkBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        // Set a central starting point
+        self.starting_position = Position {
+            x: self.map.width / 2,
+            y: self.map.height / 2,
+        };
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        self.map.tiles[start_idx] = TileType::Floor;
+
+        let total_tiles = self.map.width * self.map.height;
+        let desired_floor_tiles = (Self::FLOOR_PERCENT * total_tiles as f32) as usize;
+        let mut floor_tile_count = self
+            .map
+            .tiles
+            .iter()
+            .filter(|a| **a == TileType::Floor)
+            .count();
+        let mut digger_count = 0;
+        while floor_tile_count < desired_floor_tiles && digger_count < 500 {
+            // Every digger after the first one starts somewhere we already dug out
+            let (mut drunk_x, mut drunk_y) = if digger_count == 0 {
+                (self.starting_position.x, self.starting_position.y)
+            } else {
+                let floor_tiles: Vec<usize> = self
+                    .map
+                    .tiles
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tile)| **tile == TileType::Floor)
+                    .map(|(idx, _)| idx)
+                    .collect();
+                let idx = *rng.random_slice_entry(&floor_tiles).unwrap() as i32;
+                (idx % self.map.width, idx / self.map.width)
+            };
+            let mut drunk_life = Self::DRUNKEN_LIFETIME;
+
+            while drunk_life > 0 {
+                let drunk_idx = self.map.xy_idx(drunk_x, drunk_y);
+                self.map.tiles[drunk_idx] = TileType::Floor;
+
+                match rng.roll_dice(1, 4) {
+                    1 => {
+                        if drunk_x > 2 {
+                            drunk_x -= 1;
+                        }
+                    }
+                    2 => {
+                        if drunk_x < self.map.width - 2 {
+                            drunk_x += 1;
+                        }
+                    }
+                    3 => {
+                        if drunk_y > 2 {
+                            drunk_y -= 1;
+                        }
+                    }
+                    _ => {
+                        if drunk_y < self.map.height - 2 {
+                            drunk_y += 1;
+                        }
+                    }
+                }
+
+                drunk_life -= 1;
+            }
+
+            digger_count += 1;
+            floor_tile_count = self
+                .map
+                .tiles
+                .iter()
+                .filter(|a| **a == TileType::Floor)
+                .count();
+        }
+
+        remove_unreachable_areas(&mut self.map, start_idx);
+    }
+}
@@ -0,0 +1,105 @@
+use super::{spawner, Map, MapBuilder};
+use crate::Position;
+use rltk::RandomNumberGenerator;
+
+/// Wraps the finished level and tops up its healing. It has to come last:
+/// vaults, terrain and the connectivity check all drop spawns, and healing
+/// counted before them might not be there any more.
+pub struct HealingBuilder {
+    map: Map,
+    starting_position: Position,
+    previous_builder: Box<dyn MapBuilder>,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for HealingBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.previous_builder.build_map(rng);
+        self.map = self.previous_builder.get_map();
+        self.starting_position = self.previous_builder.get_starting_position();
+        self.spawn_list = self.previous_builder.get_spawn_list().clone();
+
+        spawner::guarantee_healing(
+            &self.map,
+            rng,
+            &self.starting_position,
+            &mut self.spawn_list,
+        );
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl HealingBuilder {
+    pub fn new(new_depth: i32, previous_builder: Box<dyn MapBuilder>) -> HealingBuilder {
+        HealingBuilder {
+            map: Map::new(new_depth, 0, 0),
+            starting_position: Position { x: 0, y: 0 },
+            previous_builder,
+            spawn_list: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::random_builder;
+    use crate::{
+        map::{spawner::MIN_HEALING, TileType},
+        raws::{self, get_healing_amount, RAWS},
+    };
+    use rltk::RandomNumberGenerator;
+
+    #[test]
+    fn finished_levels_have_enough_healing() {
+        raws::load_raws();
+        for depth in 1..=8 {
+            for seed in 1..=5 {
+                let mut rng = RandomNumberGenerator::seeded(seed * 100 + depth as u64);
+                let mut builder = random_builder(depth, &mut rng);
+                builder.build_map(&mut rng);
+                let map = builder.get_map();
+
+                let raws = RAWS.lock().unwrap();
+                let healing: Vec<&(usize, String)> = builder
+                    .get_spawn_list()
+                    .iter()
+                    .filter(|(_idx, name)| get_healing_amount(&raws, name) > 0)
+                    .collect();
+                let total: i32 = healing
+                    .iter()
+                    .map(|(_idx, name)| get_healing_amount(&raws, name))
+                    .sum();
+
+                assert!(
+                    total >= MIN_HEALING,
+                    "depth {} seed {}: only {} healing",
+                    depth,
+                    seed,
+                    total
+                );
+                for (idx, name) in healing {
+                    let tile = map.tiles[*idx];
+                    assert!(
+                        tile != TileType::Wall && !tile.is_hazard(),
+                        "{} out of reach at {} on depth {} seed {}",
+                        name,
+                        idx,
+                        depth,
+                        seed
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use super::{
+    common::{generate_voronoi_spawn_regions, remove_unreachable_areas},
+    spawner, Map, MapBuilder,
+};
+use crate::{map::TileType, Position};
+use rltk::RandomNumberGenerator;
+
+/// A perfect maze, carved with a recursive backtracker.
+pub struct MazeBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for MazeBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+
+        let noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
+        let areas: Vec<Vec<usize>> = noise_areas.into_values().collect();
+        spawner::spawn_level(
+            &self.map,
+            rng,
+            &areas,
+            &self.starting_position,
+            self.depth,
+            &mut self.spawn_list,
+        );
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl MazeBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> MazeBuilder {
+        MazeBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        // Every maze cell takes up two map tiles, so the walls between them fit in
+        let mut maze = Grid::new((self.map.width / 2) - 2, (self.map.height / 2) - 2);
+        maze.generate_maze(rng);
+        maze.copy_to_map(&mut self.map);
+
+        self.starting_position = Position { x: 2, y: 2 };
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+
+        remove_unreachable_areas(&mut self.map, start_idx);
+    }
+}
+
+const TOP: usize = 0;
+const RIGHT: usize = 1;
+const BOTTOM: usize = 2;
+const LEFT: usize = 3;
+
+#[derive(Copy, Clone)]
+struct Cell {
+    row: i32,
+    column: i32,
+    walls: [bool; 4],
+    visited: bool,
+}
+
+impl Cell {
+    fn new(row: i32, column: i32) -> Cell {
+        Cell {
+            row,
+            column,
+            walls: [true, true, true, true],
+            visited: false,
+        }
+    }
+
+    fn remove_walls(&mut self, next: &mut Cell) {
+        let x = self.column - next.column;
+        let y = self.row - next.row;
+
+        if x == 1 {
+            self.walls[LEFT] = false;
+            next.walls[RIGHT] = false;
+        } else if x == -1 {
+            self.walls[RIGHT] = false;
+            next.walls[LEFT] = false;
+        } else if y == 1 {
+            self.walls[TOP] = false;
+            next.walls[BOTTOM] = false;
+        } else if y == -1 {
+            self.walls[BOTTOM] = false;
+            next.walls[TOP] = false;
+        }
+    }
+}
+
+struct Grid {
+    width: i32,
+    height: i32,
+    cells: Vec<Cell>,
+    backtrace: Vec<usize>,
+    current: usize,
+}
+
+impl Grid {
+    fn new(width: i32, height: i32) -> Grid {
+        let mut grid = Grid {
+            width,
+            height,
+            cells: Vec::new(),
+            backtrace: Vec::new(),
+            current: 0,
+        };
+
+        for row in 0..height {
+            for column in 0..width {
+                grid.cells.push(Cell::new(row, column));
+            }
+        }
+
+        grid
+    }
+
+    fn calculate_index(&self, row: i32, column: i32) -> Option<usize> {
+        if row < 0 || column < 0 || column > self.width - 1 || row > self.height - 1 {
+            None
+        } else {
+            Some((column + (row * self.width)) as usize)
+        }
+    }
+
+    fn get_available_neighbors(&self) -> Vec<usize> {
+        let current_row = self.cells[self.current].row;
+        let current_column = self.cells[self.current].column;
+
+        [
+            self.calculate_index(current_row - 1, current_column),
+            self.calculate_index(current_row, current_column + 1),
+            self.calculate_index(current_row + 1, current_column),
+            self.calculate_index(current_row, current_column - 1),
+        ]
+        .iter()
+        .flatten()
+        .filter(|i| !self.cells[**i].visited)
+        .copied()
+        .collect()
+    }
+
+    fn find_next_cell(&self, rng: &mut RandomNumberGenerator) -> Option<usize> {
+        let neighbors = self.get_available_neighbors();
+        rng.random_slice_entry(&neighbors).copied()
+    }
+
+    fn generate_maze(&mut self, rng: &mut RandomNumberGenerator) {
+        loop {
+            self.cells[self.current].visited = true;
+
+            match self.find_next_cell(rng) {
+                Some(next) => {
+                    self.cells[next].visited = true;
+                    self.backtrace.push(self.current);
+
+                    let (lower, upper) = self.cells.split_at_mut(usize::max(self.current, next));
+                    let (cell1, cell2) = if self.current < next {
+                        (&mut lower[self.current], &mut upper[0])
+                    } else {
+                        (&mut upper[0], &mut lower[next])
+                    };
+                    cell1.remove_walls(cell2);
+
+                    self.current = next;
+                }
+                None => match self.backtrace.pop() {
+                    Some(previous) => self.current = previous,
+                    None => break,
+                },
+            }
+        }
+    }
+
+    fn copy_to_map(&self, map: &mut Map) {
+        // Clear the map
+        for tile in map.tiles.iter_mut() {
+            *tile = TileType::Wall;
+        }
+
+        for cell in self.cells.iter() {
+            let x = cell.column + 1;
+            let y = cell.row + 1;
+            let idx = map.xy_idx(x * 2, y * 2);
+
+            map.tiles[idx] = TileType::Floor;
+            if !cell.walls[TOP] {
+                map.tiles[idx - map.width as usize] = TileType::Floor
+            }
+            if !cell.walls[RIGHT] {
+                map.tiles[idx + 1] = TileType::Floor
+            }
+            if !cell.walls[BOTTOM] {
+                map.tiles[idx + map.width as usize] = TileType::Floor
+            }
+            if !cell.walls[LEFT] {
+                map.tiles[idx - 1] = TileType::Floor
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::{spawner, Map};
+use crate::Position;
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+mod common;
+
+mod simple_map;
+use simple_map::SimpleMapBuilder;
+
+mod bsp_dungeon;
+use bsp_dungeon::BspDungeonBuilder;
+
+mod cellular_automata;
+use cellular_automata::CellularAutomataBuilder;
+
+mod drunkard;
+use drunkard::DrunkardsWalkBuilder;
+
+mod maze;
+use maze::MazeBuilder;
+
+mod voronoi;
+use voronoi::VoronoiCellBuilder;
+
+mod prefab_builder;
+use prefab_builder::PrefabBuilder;
+
+mod terrain;
+use terrain::TerrainBuilder;
+
+mod connectivity;
+use connectivity::ConnectivityBuilder;
+
+mod healing;
+use healing::HealingBuilder;
+
+pub trait MapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator);
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> Position;
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>;
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        // Packs go last, so their followers can gather around whatever is already there
+        let (packs, others): (Vec<_>, Vec<_>) = self
+            .get_spawn_list()
+            .iter()
+            .partition(|spawn| spawner::is_pack(&spawn.1));
+        for entity in others.into_iter().chain(packs) {
+            spawner::spawn_entity(ecs, entity);
+        }
+    }
+}
+
+/// Picks a map builder for the given depth. The first levels stick to rooms,
+/// deeper levels can also turn into caves, mazes and hives. Whatever gets
+/// picked may have prefab vaults stamped into it and water, lava or chasms
+/// spread over it afterwards, then gets its connectivity checked and its exit
+/// placed, and finally has its healing topped up.
+pub fn random_builder(new_depth: i32, rng: &mut RandomNumberGenerator) -> Box<dyn MapBuilder> {
+    let (width, height) = map_size(new_depth, rng);
+    let builder = base_builder(new_depth, width, height, rng);
+    let builder = Box::new(PrefabBuilder::new(new_depth, builder));
+    let builder = Box::new(TerrainBuilder::new(new_depth, builder));
+    let builder = Box::new(ConnectivityBuilder::new(new_depth, builder));
+    Box::new(HealingBuilder::new(new_depth, builder))
+}
+
+/// Maps start out a bit larger than the screen and keep growing with depth,
+/// the camera scrolls over whatever doesn't fit.
+fn map_size(new_depth: i32, rng: &mut RandomNumberGenerator) -> (i32, i32) {
+    let growth = i32::min(new_depth - 1, 8);
+    let width = 80 + growth * 5 + rng.range(0, 21);
+    let height = 50 + growth * 3 + rng.range(0, 11);
+    (width, height)
+}
+
+fn base_builder(
+    new_depth: i32,
+    width: i32,
+    height: i32,
+    rng: &mut RandomNumberGenerator,
+) -> Box<dyn MapBuilder> {
+    let available_builders = match new_depth {
+        1..=2 => 2,
+        3..=4 => 4,
+        _ => 6,
+    };
+
+    match rng.roll_dice(1, available_builders) {
+        1 => Box::new(SimpleMapBuilder::new(new_depth, width, height)),
+        2 => Box::new(BspDungeonBuilder::new(new_depth, width, height)),
+        3 => Box::new(CellularAutomataBuilder::new(new_depth, width, height)),
+        4 => Box::new(DrunkardsWalkBuilder::new(new_depth, width, height)),
+        5 => Box::new(MazeBuilder::new(new_depth, width, height)),
+        _ => Box::new(VoronoiCellBuilder::new(new_depth, width, height)),
+    }
+}
@@ -0,0 +1,217 @@
+//! Stamps hand-authored set pieces into a finished map.
+//!
+//! Prefabs are REX Paint (.xp) files in `resources/prefabs`; only the first
+//! layer is read. The glyphs map to tiles and spawns like this:
+//!
+//! | Glyph | Meaning                                  |
+//! |-------|------------------------------------------|
+//! | ` `   | leave the underlying map alone           |
+//! | `#`   | wall                                     |
+//! | `.`   | floor                                    |
+//! | `g`   | Goblin                                   |
+//! | `o`   | Orc                                      |
+//! | `!`   | Health Potion                            |
+//! | `?`   | Magic Missile Scroll                     |
+//! | `*`   | Fireball Scroll                          |
+//! | `&`   | Confusion Scroll                         |
+//! | `/`   | Dagger                                   |
+//! | `\|`  | Longsword                                |
+//! | `(`   | Shield                                   |
+//! | `[`   | Tower Shield                             |
+//! | `^`   | Spike Trap (hidden)                      |
+//!
+//! Anything placed on an entity glyph stands on floor.
+
+use super::{Map, MapBuilder};
+use crate::{map::TileType, Position};
+use rltk::{rex::XpFile, RandomNumberGenerator};
+use std::{fs, fs::File, path::Path};
+
+const PREFAB_PATH: &str = "./resources/prefabs";
+
+pub struct Prefab {
+    pub width: i32,
+    pub height: i32,
+    glyphs: Vec<char>,
+}
+
+impl Prefab {
+    fn glyph(&self, x: i32, y: i32) -> char {
+        self.glyphs[(y * self.width + x) as usize]
+    }
+}
+
+/// Reads every .xp file in the given directory. The result is sorted by file
+/// name, so the same seed always picks the same prefabs.
+pub fn load_prefabs(path: &str) -> Vec<Prefab> {
+    let mut prefabs = Vec::new();
+    let entries = match fs::read_dir(Path::new(path)) {
+        Ok(entries) => entries,
+        Err(_) => return prefabs,
+    };
+
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|file| file.extension().map(|ext| ext == "xp").unwrap_or(false))
+        .collect();
+    files.sort();
+
+    for file in files.iter() {
+        let xp = File::open(file).and_then(|mut f| XpFile::read(&mut f));
+        if let Ok(xp) = xp {
+            if let Some(layer) = xp.layers.first() {
+                let mut glyphs = Vec::with_capacity(layer.width * layer.height);
+                for y in 0..layer.height {
+                    for x in 0..layer.width {
+                        let cell = layer.get(x, y).unwrap();
+                        let glyph = if cell.ch == 0 || cell.ch > 255 {
+                            ' '
+                        } else {
+                            rltk::to_char(cell.ch as u8)
+                        };
+                        glyphs.push(glyph);
+                    }
+                }
+
+                prefabs.push(Prefab {
+                    width: layer.width as i32,
+                    height: layer.height as i32,
+                    glyphs,
+                });
+            }
+        }
+    }
+
+    prefabs
+}
+
+/// What a glyph turns into: the tile to place and optionally something to spawn on it.
+fn glyph_meaning(glyph: char) -> Option<(TileType, Option<&'static str>)> {
+    match glyph {
+        ' ' => None,
+        '#' => Some((TileType::Wall, None)),
+        'g' => Some((TileType::Floor, Some("Goblin"))),
+        'o' => Some((TileType::Floor, Some("Orc"))),
+        '!' => Some((TileType::Floor, Some("Health Potion"))),
+        '?' => Some((TileType::Floor, Some("Magic Missile Scroll"))),
+        '*' => Some((TileType::Floor, Some("Fireball Scroll"))),
+        '&' => Some((TileType::Floor, Some("Confusion Scroll"))),
+        '/' => Some((TileType::Floor, Some("Dagger"))),
+        '|' => Some((TileType::Floor, Some("Longsword"))),
+        '(' => Some((TileType::Floor, Some("Shield"))),
+        '[' => Some((TileType::Floor, Some("Tower Shield"))),
+        '^' => Some((TileType::Floor, Some("Spike Trap"))),
+        _ => Some((TileType::Floor, None)),
+    }
+}
+
+/// Wraps another builder and stamps prefab vaults into the open spaces of
+/// whatever it built.
+pub struct PrefabBuilder {
+    map: Map,
+    starting_position: Position,
+    previous_builder: Box<dyn MapBuilder>,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for PrefabBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.previous_builder.build_map(rng);
+        self.map = self.previous_builder.get_map();
+        self.starting_position = self.previous_builder.get_starting_position();
+        self.spawn_list = self.previous_builder.get_spawn_list().clone();
+
+        self.apply_vaults(rng);
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl PrefabBuilder {
+    pub fn new(new_depth: i32, previous_builder: Box<dyn MapBuilder>) -> PrefabBuilder {
+        PrefabBuilder {
+            map: Map::new(new_depth, 0, 0),
+            starting_position: Position { x: 0, y: 0 },
+            previous_builder,
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn apply_vaults(&mut self, rng: &mut RandomNumberGenerator) {
+        let prefabs = load_prefabs(PREFAB_PATH);
+        if prefabs.is_empty() {
+            return;
+        }
+
+        let n_vaults = rng.roll_dice(1, 3) - 1;
+        for _i in 0..n_vaults {
+            let vault = rng.random_slice_entry(&prefabs).unwrap();
+
+            let candidates = self.vault_positions(vault);
+            if let Some(top_left) = rng.random_slice_entry(&candidates) {
+                self.stamp(vault, top_left.0, top_left.1);
+            }
+        }
+    }
+
+    /// Every top-left corner where the vault, and a ring of one tile around it,
+    /// lies entirely on plain floor away from the start. The ring keeps the
+    /// vault reachable from all sides.
+    fn vault_positions(&self, vault: &Prefab) -> Vec<(i32, i32)> {
+        let mut candidates = Vec::new();
+        for y in 2..self.map.height - vault.height - 2 {
+            for x in 2..self.map.width - vault.width - 2 {
+                let mut possible = true;
+                'search: for ty in y - 1..=y + vault.height {
+                    for tx in x - 1..=x + vault.width {
+                        let idx = self.map.xy_idx(tx, ty);
+                        if self.map.tiles[idx] != TileType::Floor
+                            || (tx == self.starting_position.x && ty == self.starting_position.y)
+                        {
+                            possible = false;
+                            break 'search;
+                        }
+                    }
+                }
+
+                if possible {
+                    candidates.push((x, y));
+                }
+            }
+        }
+
+        candidates
+    }
+
+    fn stamp(&mut self, vault: &Prefab, x: i32, y: i32) {
+        let width = self.map.width;
+        self.spawn_list.retain(|(idx, _)| {
+            let sx = *idx as i32 % width;
+            let sy = *idx as i32 / width;
+            sx < x || sx >= x + vault.width || sy < y || sy >= y + vault.height
+        });
+
+        for ty in 0..vault.height {
+            for tx in 0..vault.width {
+                if let Some((tile, spawn)) = glyph_meaning(vault.glyph(tx, ty)) {
+                    let idx = self.map.xy_idx(x + tx, y + ty);
+                    self.map.tiles[idx] = tile;
+                    if let Some(name) = spawn {
+                        self.spawn_list.push((idx, name.to_string()));
+                    }
+                }
+            }
+        }
+    }
+}
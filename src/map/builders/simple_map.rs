@@ -0,0 +1,124 @@
+use super::{
+    common::{apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, place_doors},
+    spawner, Map, MapBuilder,
+};
+use crate::Position;
+use rltk::{RandomNumberGenerator, Rect};
+
+/// The classic generator: random rooms joined by L-shaped corridors.
+pub struct SimpleMapBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    rooms: Vec<Rect>,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.rooms_and_corridors(rng);
+
+        let areas: Vec<Vec<usize>> = self
+            .rooms
+            .iter()
+            .map(|room| spawner::room_tiles(&self.map, room))
+            .collect();
+        spawner::spawn_level(
+            &self.map,
+            rng,
+            &areas,
+            &self.starting_position,
+            self.depth,
+            &mut self.spawn_list,
+        );
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl SimpleMapBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> SimpleMapBuilder {
+        SimpleMapBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            rooms: Vec::new(),
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn rooms_and_corridors(&mut self, rng: &mut RandomNumberGenerator) {
+        const MAX_ROOMS: i32 = 30;
+        const MIN_SIZE: i32 = 6;
+        const MAX_SIZE: i32 = 10;
+
+        for _i in 0..MAX_ROOMS {
+            let w = rng.range(MIN_SIZE, MAX_SIZE);
+            let h = rng.range(MIN_SIZE, MAX_SIZE);
+            let x = rng.roll_dice(1, self.map.width - w - 1) - 1;
+            let y = rng.roll_dice(1, self.map.height - h - 1) - 1;
+            let new_room = Rect::with_size(x, y, w, h);
+            let mut ok = true;
+            for other_room in self.rooms.iter() {
+                if new_room.intersect(other_room) {
+                    ok = false
+                }
+            }
+            if ok {
+                apply_room_to_map(&mut self.map, &new_room);
+
+                if !self.rooms.is_empty() {
+                    let new_center = new_room.center();
+                    let prev_center = self.rooms[self.rooms.len() - 1].center();
+                    if rng.range(0, 2) == 1 {
+                        apply_horizontal_tunnel(
+                            &mut self.map,
+                            prev_center.x,
+                            new_center.x,
+                            prev_center.y,
+                        );
+                        apply_vertical_tunnel(
+                            &mut self.map,
+                            prev_center.y,
+                            new_center.y,
+                            new_center.x,
+                        );
+                    } else {
+                        apply_vertical_tunnel(
+                            &mut self.map,
+                            prev_center.y,
+                            new_center.y,
+                            prev_center.x,
+                        );
+                        apply_horizontal_tunnel(
+                            &mut self.map,
+                            prev_center.x,
+                            new_center.x,
+                            new_center.y,
+                        );
+                    }
+                }
+
+                self.rooms.push(new_room);
+            }
+        }
+
+        let start_pos = self.rooms[0].center();
+        self.starting_position = Position {
+            x: start_pos.x,
+            y: start_pos.y,
+        };
+        place_doors(&mut self.map, &self.rooms);
+        self.map.rooms = self.rooms.clone();
+    }
+}
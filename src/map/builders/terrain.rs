@@ -0,0 +1,210 @@
+use super::{Map, MapBuilder};
+use crate::{map::TileType, Position};
+use rltk::RandomNumberGenerator;
+
+/// Tiles within this distance of the start are never turned into terrain, so
+/// the player doesn't begin a level knee-deep in lava.
+const START_CLEARANCE: f32 = 4.0;
+
+/// Wraps another builder and floods parts of its floor with water, lava and
+/// chasms. Whenever a hazard cuts off part of the level, bridges are laid
+/// across it so everything stays reachable.
+pub struct TerrainBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    previous_builder: Box<dyn MapBuilder>,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for TerrainBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.previous_builder.build_map(rng);
+        self.map = self.previous_builder.get_map();
+        self.starting_position = self.previous_builder.get_starting_position();
+        self.spawn_list = self.previous_builder.get_spawn_list().clone();
+
+        self.add_terrain(rng);
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl TerrainBuilder {
+    pub fn new(new_depth: i32, previous_builder: Box<dyn MapBuilder>) -> TerrainBuilder {
+        TerrainBuilder {
+            map: Map::new(new_depth, 0, 0),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            previous_builder,
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn add_terrain(&mut self, rng: &mut RandomNumberGenerator) {
+        let original = self.map.clone();
+
+        let n_pools = rng.roll_dice(1, 3) - 1;
+        for _i in 0..n_pools {
+            let size = rng.range(60, 160);
+            self.add_pool(rng, size);
+        }
+
+        if self.depth >= 3 && rng.roll_dice(1, 3) == 1 {
+            let size = rng.range(60, 150);
+            let chasm = self.paint_blob(rng, size);
+            for idx in chasm {
+                self.map.tiles[idx] = TileType::Chasm;
+            }
+        }
+
+        if self.depth >= 4 {
+            let n_lava = rng.roll_dice(1, 3) - 1;
+            for _i in 0..n_lava {
+                let size = rng.range(15, 50);
+                let lava = self.paint_blob(rng, size);
+                for idx in lava {
+                    self.map.tiles[idx] = TileType::Lava;
+                }
+            }
+        }
+
+        self.build_bridges(&original);
+
+        let map = &self.map;
+        self.spawn_list
+            .retain(|(idx, _name)| !map.tiles[*idx].is_hazard());
+    }
+
+    /// Water is deep in the middle of a pool and shallow around its edges.
+    fn add_pool(&mut self, rng: &mut RandomNumberGenerator, size: i32) {
+        let pool = self.paint_blob(rng, size);
+        let w = self.map.width as usize;
+        for idx in pool.iter() {
+            let surrounded = [
+                idx - 1,
+                idx + 1,
+                idx - w,
+                idx + w,
+                idx - w - 1,
+                idx - w + 1,
+                idx + w - 1,
+                idx + w + 1,
+            ]
+            .iter()
+            .all(|neighbor| pool.contains(neighbor));
+            self.map.tiles[*idx] = if surrounded {
+                TileType::DeepWater
+            } else {
+                TileType::ShallowWater
+            };
+        }
+    }
+
+    /// Staggers around from a random floor tile and returns the floor tiles it
+    /// walked over. Walls, doors and stairs are left as they are.
+    fn paint_blob(&self, rng: &mut RandomNumberGenerator, size: i32) -> Vec<usize> {
+        let start = self.starting_position.clone();
+        let can_paint = |map: &Map, x: i32, y: i32| {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] == TileType::Floor
+                && rltk::DistanceAlg::Pythagoras
+                    .distance2d(rltk::Point::new(x, y), rltk::Point::new(start.x, start.y))
+                    > START_CLEARANCE
+        };
+
+        let floor: Vec<usize> = (0..self.map.tiles.len())
+            .filter(|idx| {
+                can_paint(
+                    &self.map,
+                    *idx as i32 % self.map.width,
+                    *idx as i32 / self.map.width,
+                )
+            })
+            .collect();
+        let origin = match rng.random_slice_entry(&floor) {
+            Some(idx) => *idx,
+            None => return Vec::new(),
+        };
+
+        let mut blob = vec![origin];
+        let mut x = origin as i32 % self.map.width;
+        let mut y = origin as i32 / self.map.width;
+        for _step in 0..size {
+            match rng.roll_dice(1, 4) {
+                1 => x += 1,
+                2 => x -= 1,
+                3 => y += 1,
+                _ => y -= 1,
+            }
+            x = x.clamp(1, self.map.width - 2);
+            y = y.clamp(1, self.map.height - 2);
+            let idx = self.map.xy_idx(x, y);
+            if can_paint(&self.map, x, y) && !blob.contains(&idx) {
+                blob.push(idx);
+            }
+        }
+
+        blob
+    }
+
+    /// Pathfinding won't cross hazards, so a chasm or lava flow can cut a level
+    /// in two. Lay bridges along the way the player could have walked before
+    /// until every formerly reachable tile can be reached again.
+    fn build_bridges(&mut self, original: &Map) {
+        const MAX_BRIDGES: i32 = 20;
+
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        let mut original = original.clone();
+        original.populate_blocked();
+        let was_reachable = distances_from(&original, start_idx);
+
+        for _i in 0..MAX_BRIDGES {
+            self.map.populate_blocked();
+            let reachable = distances_from(&self.map, start_idx);
+            let cut_off = self.map.tiles.iter().enumerate().position(|(idx, tile)| {
+                *tile != TileType::Wall
+                    && !tile.is_hazard()
+                    && reachable[idx] == f32::MAX
+                    && was_reachable[idx] != f32::MAX
+            });
+            let target = match cut_off {
+                Some(idx) => idx,
+                None => break,
+            };
+
+            let path = rltk::a_star_search(start_idx, target, &original);
+            if !path.success {
+                break;
+            }
+            for step in path.steps.iter() {
+                if self.map.tiles[*step].is_hazard() {
+                    self.map.tiles[*step] = TileType::Bridge;
+                }
+            }
+        }
+    }
+}
+
+fn distances_from(map: &Map, start_idx: usize) -> Vec<f32> {
+    rltk::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &[start_idx],
+        map,
+        (map.width * map.height) as f32,
+    )
+    .map
+}
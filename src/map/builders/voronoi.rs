@@ -0,0 +1,115 @@
+use super::{
+    common::{generate_voronoi_spawn_regions, nearest_floor_tile, remove_unreachable_areas},
+    spawner, Map, MapBuilder,
+};
+use crate::{map::TileType, Position};
+use rltk::{DistanceAlg, Point, RandomNumberGenerator};
+
+/// A "hive" of cells: every tile belongs to its closest seed point, and walls
+/// grow along the borders between the cells.
+pub struct VoronoiCellBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl MapBuilder for VoronoiCellBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.build(rng);
+
+        let noise_areas = generate_voronoi_spawn_regions(&self.map, rng);
+        let areas: Vec<Vec<usize>> = noise_areas.into_values().collect();
+        spawner::spawn_level(
+            &self.map,
+            rng,
+            &areas,
+            &self.starting_position,
+            self.depth,
+            &mut self.spawn_list,
+        );
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)> {
+        &self.spawn_list
+    }
+}
+
+impl VoronoiCellBuilder {
+    const N_SEEDS: usize = 64;
+
+    pub fn new(new_depth: i32, width: i32, height: i32) -> VoronoiCellBuilder {
+        VoronoiCellBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            spawn_list: Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng: &mut RandomNumberGenerator) {
+        // Scatter the seeds
+        let mut voronoi_seeds: Vec<(usize, Point)> = Vec::new();
+        while voronoi_seeds.len() < Self::N_SEEDS {
+            let vx = rng.roll_dice(1, self.map.width - 1);
+            let vy = rng.roll_dice(1, self.map.height - 1);
+            let vidx = self.map.xy_idx(vx, vy);
+            let candidate = (vidx, Point::new(vx, vy));
+            if !voronoi_seeds.contains(&candidate) {
+                voronoi_seeds.push(candidate);
+            }
+        }
+
+        // Assign every tile to its closest seed
+        let mut voronoi_membership: Vec<i32> = vec![0; (self.map.width * self.map.height) as usize];
+        for (i, vid) in voronoi_membership.iter_mut().enumerate() {
+            let x = i as i32 % self.map.width;
+            let y = i as i32 / self.map.width;
+
+            let mut closest = (0, f32::MAX);
+            for (seed, pos) in voronoi_seeds.iter().enumerate() {
+                let distance = DistanceAlg::Pythagoras.distance2d(Point::new(x, y), pos.1);
+                if distance < closest.1 {
+                    closest = (seed, distance);
+                }
+            }
+
+            *vid = closest.0 as i32;
+        }
+
+        // Tiles bordering more than one other cell become walls
+        for y in 1..self.map.height - 1 {
+            for x in 1..self.map.width - 1 {
+                let my_idx = self.map.xy_idx(x, y);
+                let my_seed = voronoi_membership[my_idx];
+                let neighbors = [
+                    self.map.xy_idx(x - 1, y),
+                    self.map.xy_idx(x + 1, y),
+                    self.map.xy_idx(x, y - 1),
+                    self.map.xy_idx(x, y + 1),
+                ]
+                .iter()
+                .filter(|idx| voronoi_membership[**idx] != my_seed)
+                .count();
+
+                if neighbors < 2 {
+                    self.map.tiles[my_idx] = TileType::Floor;
+                }
+            }
+        }
+
+        let (x, y) = nearest_floor_tile(&self.map, self.map.width / 2, self.map.height / 2);
+        self.starting_position = Position { x, y };
+
+        let start_idx = self.map.xy_idx(x, y);
+        remove_unreachable_areas(&mut self.map, start_idx);
+    }
+}
@@ -0,0 +1,209 @@
+use super::{Map, MasterSeed};
+use crate::{
+    components::{OtherLevelPosition, ParticleLifetime, Position},
+    raws::{get_unidentified_items, RAWS},
+};
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const POTION_LOOKS: [(&str, &str); 10] = [
+    ("murky green", "#556B2F"),
+    ("bubbling red", "#DC143C"),
+    ("cloudy white", "#F5F5F5"),
+    ("fizzy blue", "#1E90FF"),
+    ("oily black", "#696969"),
+    ("glowing amber", "#FFBF00"),
+    ("smoky grey", "#A9A9A9"),
+    ("sparkling pink", "#FF69B4"),
+    ("thick brown", "#8B4513"),
+    ("pale yellow", "#FFFFE0"),
+];
+const SCROLL_SYLLABLES: [&str; 16] = [
+    "xy", "zzy", "ka", "mor", "thu", "nix", "el", "vor", "qua", "ble", "zan", "ok", "ru", "fen",
+    "ix", "dra",
+];
+const SCROLL_COLOUR: &str = "#F5DEB3";
+
+/// How an unidentified item type looks this run.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Appearance {
+    pub name: String,
+    pub colour: String,
+}
+
+/// Every level the player has visited so far, by depth. Levels are stored
+/// when the player leaves them and handed back when they return. It also
+/// remembers what else is particular to this run: which unique monsters have
+/// been killed, what the potions and scrolls look like, and which of them the
+/// player has figured out.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct MasterDungeonMap {
+    maps: HashMap<i32, Map>,
+    #[serde(default)]
+    slain_uniques: HashSet<String>,
+    #[serde(default)]
+    appearances: HashMap<String, Appearance>,
+    #[serde(default)]
+    identified: HashSet<String>,
+}
+
+impl MasterDungeonMap {
+    /// Starts a run, handing out this seed's disguises to every item type
+    /// the raws want unidentified.
+    pub fn new(seed: &MasterSeed) -> MasterDungeonMap {
+        // Depth 0 is never built, so its generator is free for run-wide rolls
+        let mut rng = seed.rng_for_depth(0);
+        MasterDungeonMap {
+            maps: HashMap::new(),
+            slain_uniques: HashSet::new(),
+            appearances: assign_appearances(&mut rng),
+            identified: HashSet::new(),
+        }
+    }
+
+    /// What the player calls an item: its disguise until its type has been
+    /// identified, its real name afterwards.
+    pub fn display_name(&self, name: &str) -> String {
+        match self.appearances.get(name) {
+            Some(appearance) if !self.identified.contains(name) => appearance.name.clone(),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Returns the disguise of an item type, if it has one.
+    pub fn appearance(&self, name: &str) -> Option<&Appearance> {
+        self.appearances.get(name)
+    }
+
+    /// Tells whether the player still doesn't know what this item type is.
+    pub fn is_unidentified(&self, name: &str) -> bool {
+        self.appearances.contains_key(name) && !self.identified.contains(name)
+    }
+
+    pub fn identify(&mut self, name: &str) {
+        self.identified.insert(name.to_string());
+    }
+
+    pub fn record_slain_unique(&mut self, name: &str) {
+        self.slain_uniques.insert(name.to_string());
+    }
+
+    pub fn is_unique_slain(&self, name: &str) -> bool {
+        self.slain_uniques.contains(name)
+    }
+
+    pub fn store_map(&mut self, map: &Map) {
+        self.maps.insert(map.depth, map.clone());
+    }
+
+    pub fn get_map(&self, depth: i32) -> Option<Map> {
+        self.maps.get(&depth).map(|map| {
+            let mut map = map.clone();
+            map.tile_content = vec![Vec::new(); (map.width * map.height) as usize];
+            map
+        })
+    }
+}
+
+/// Makes up a distinct disguise for every item type the raws want
+/// unidentified: a colour for potions, a nonsense label for scrolls.
+fn assign_appearances(rng: &mut RandomNumberGenerator) -> HashMap<String, Appearance> {
+    let mut potion_looks: Vec<(&str, &str)> = POTION_LOOKS.to_vec();
+    let mut labels_used: HashSet<String> = HashSet::new();
+    let mut appearances = HashMap::new();
+
+    for (name, kind) in get_unidentified_items(&RAWS.lock().unwrap()) {
+        let appearance = if kind == "scroll" {
+            let label = loop {
+                let syllables = rng.roll_dice(2, 2);
+                let label: String = (0..syllables)
+                    .map(|_| *rng.random_slice_entry(&SCROLL_SYLLABLES).unwrap())
+                    .collect::<String>()
+                    .to_uppercase();
+                if labels_used.insert(label.clone()) {
+                    break label;
+                }
+            };
+            Appearance {
+                name: format!("scroll labelled {}", label),
+                colour: SCROLL_COLOUR.to_string(),
+            }
+        } else {
+            let (look, colour) = if potion_looks.is_empty() {
+                ("strange", "#FFFFFF")
+            } else {
+                let index = (rng.roll_dice(1, potion_looks.len() as i32) - 1) as usize;
+                potion_looks.remove(index)
+            };
+            Appearance {
+                name: format!("{} {}", look, kind),
+                colour: colour.to_string(),
+            }
+        };
+        appearances.insert(name, appearance);
+    }
+
+    appearances
+}
+
+/// Takes everything on the current level except the player off the map, by
+/// swapping its `Position` for an `OtherLevelPosition`. Particles are simply dropped.
+pub fn freeze_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let particles = ecs.read_storage::<ParticleLifetime>();
+    let player_entity = ecs.fetch::<Entity>();
+    let map_depth = ecs.fetch::<Map>().depth;
+
+    let mut pos_to_delete: Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &positions).join() {
+        if entity == *player_entity {
+            continue;
+        }
+        if particles.get(entity).is_some() {
+            entities.delete(entity).expect("Unable to delete particle");
+            continue;
+        }
+
+        other_level_positions
+            .insert(
+                entity,
+                OtherLevelPosition {
+                    x: pos.x,
+                    y: pos.y,
+                    depth: map_depth,
+                },
+            )
+            .expect("Insert fail");
+        pos_to_delete.push(entity);
+    }
+
+    for entity in pos_to_delete.iter() {
+        positions.remove(*entity);
+    }
+}
+
+/// Puts everything that was frozen on the current depth back on the map.
+pub fn thaw_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let map_depth = ecs.fetch::<Map>().depth;
+
+    let mut pos_to_delete: Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &other_level_positions).join() {
+        if pos.depth == map_depth {
+            positions
+                .insert(entity, Position { x: pos.x, y: pos.y })
+                .expect("Insert fail");
+            pos_to_delete.push(entity);
+        }
+    }
+
+    for entity in pos_to_delete.iter() {
+        other_level_positions.remove(*entity);
+    }
+}
@@ -1,20 +1,38 @@
-use rltk::{Algorithm2D, BaseMap, Point, RandomNumberGenerator, Rect, Rltk, RGB};
+use rltk::{Algorithm2D, BaseMap, Point, Rect, RGB};
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
-use std::{
-    cmp::{max, min},
-    collections::HashSet,
-};
-
-pub const MAPWIDTH: usize = 80;
-pub const MAPHEIGHT: usize = 43;
-pub const MAPCOUNT: usize = MAPHEIGHT * MAPWIDTH;
+use std::collections::HashSet;
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    UpStairs,
+    ClosedDoor,
+    OpenDoor,
+    ShallowWater,
+    DeepWater,
+    Lava,
+    Chasm,
+    Bridge,
+}
+
+impl TileType {
+    /// How much stepping onto the tile costs, relative to plain floor.
+    pub fn movement_cost(&self) -> f32 {
+        match self {
+            TileType::ShallowWater => 1.5,
+            TileType::DeepWater => 3.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Tiles that hurt or swallow whoever steps on them. Pathfinding never
+    /// routes through them, though the player is free to walk in.
+    pub fn is_hazard(&self) -> bool {
+        matches!(self, TileType::Lava | TileType::Chasm)
+    }
 }
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -39,41 +57,16 @@ impl Map {
         (y as usize * self.width as usize) + x as usize
     }
 
-    fn apply_room_to_map(&mut self, room: &Rect) {
-        for y in room.y1 + 1..=room.y2 {
-            for x in room.x1 + 1..=room.x2 {
-                let idx = self.xy_idx(x, y);
-                self.tiles[idx] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_horizontal_tunnel(&mut self, x1: i32, x2: i32, y: i32) {
-        for x in min(x1, x2)..=max(x1, x2) {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_vertical_tunnel(&mut self, y1: i32, y2: i32, x: i32) {
-        for y in min(y1, y2)..=max(y1, y2) {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
-            }
-        }
-    }
-
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
         if x < 1 || x > self.width - 1 || y < 1 || y > self.height - 1 {
             return false;
         }
         let idx = self.xy_idx(x, y);
-        !self.blocked[idx]
+        !self.blocked[idx] && !self.tiles[idx].is_hazard()
     }
 
+    /// Closed doors don't count as blocked: whoever walks into one opens it,
+    /// so pathfinding may route through them.
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
             self.blocked[i] = *tile == TileType::Wall;
@@ -86,75 +79,40 @@ impl Map {
         }
     }
 
-    pub fn new_map_rooms_and_corridors(new_depth: i32) -> Map {
-        let mut map = Map {
-            tiles: vec![TileType::Wall; MAPCOUNT],
+    /// Generates an empty map, consisting entirely of solid walls. The size is
+    /// independent of the screen; the camera only ever shows part of it.
+    pub fn new(new_depth: i32, width: i32, height: i32) -> Map {
+        let map_tile_count = (width * height) as usize;
+        Map {
+            tiles: vec![TileType::Wall; map_tile_count],
             rooms: Vec::new(),
-            width: MAPWIDTH as i32,
-            height: MAPHEIGHT as i32,
-            revealed_tiles: vec![false; MAPCOUNT],
-            visible_tiles: vec![false; MAPCOUNT],
-            blocked: vec![false; MAPCOUNT],
-            tile_content: vec![Vec::new(); MAPCOUNT],
+            width,
+            height,
+            revealed_tiles: vec![false; map_tile_count],
+            visible_tiles: vec![false; map_tile_count],
+            blocked: vec![false; map_tile_count],
+            tile_content: vec![Vec::new(); map_tile_count],
             depth: new_depth,
             bloodstains: HashSet::new(),
-        };
-
-        const MAX_ROOMS: i32 = 30;
-        const MIN_SIZE: i32 = 6;
-        const MAX_SIZE: i32 = 10;
-
-        let mut rng = RandomNumberGenerator::new();
-
-        for _i in 0..MAX_ROOMS {
-            let w = rng.range(MIN_SIZE, MAX_SIZE);
-            let h = rng.range(MIN_SIZE, MAX_SIZE);
-            let x = rng.roll_dice(1, map.width - w - 1) - 1;
-            let y = rng.roll_dice(1, map.height - h - 1) - 1;
-            let new_room = Rect::with_size(x, y, w, h);
-            let mut ok = true;
-            for other_room in map.rooms.iter() {
-                if new_room.intersect(other_room) {
-                    ok = false
-                }
-            }
-            if ok {
-                map.apply_room_to_map(&new_room);
-
-                if !map.rooms.is_empty() {
-                    let new_center = new_room.center();
-                    let prev_center = map.rooms[map.rooms.len() - 1].center();
-                    if rng.range(0, 2) == 1 {
-                        map.apply_horizontal_tunnel(prev_center.x, new_center.x, prev_center.y);
-                        map.apply_vertical_tunnel(prev_center.y, new_center.y, new_center.x);
-                    } else {
-                        map.apply_vertical_tunnel(prev_center.y, new_center.y, prev_center.x);
-                        map.apply_horizontal_tunnel(prev_center.x, new_center.x, new_center.y);
-                    }
-                }
-
-                map.rooms.push(new_room);
-            }
         }
-
-        let stairs_position = map.rooms[map.rooms.len() - 1].center();
-        let stairs_idx = map.xy_idx(stairs_position.x, stairs_position.y);
-        map.tiles[stairs_idx] = TileType::DownStairs;
-
-        map
     }
 }
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx] == TileType::Wall
+        matches!(self.tiles[idx], TileType::Wall | TileType::ClosedDoor)
     }
 
     fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
         let w = self.width as usize;
         let p1 = Point::new(idx1 % w, idx1 / w);
         let p2 = Point::new(idx2 % w, idx2 / w);
-        rltk::DistanceAlg::Pythagoras.distance2d(p1, p2)
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(p1, p2);
+        if distance < 1.0 {
+            return distance;
+        }
+        // However we get there, the last step is onto idx2
+        distance - 1.0 + self.tiles[idx2].movement_cost()
     }
 
     fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
@@ -163,31 +121,33 @@ impl BaseMap for Map {
         let y = idx as i32 / self.width;
         let w = self.width as usize;
 
+        let cost = |exit: usize, base: f32| base * self.tiles[exit].movement_cost();
+
         if self.is_exit_valid(x - 1, y) {
-            exits.push((idx - 1, 1.0))
+            exits.push((idx - 1, cost(idx - 1, 1.0)))
         };
         if self.is_exit_valid(x + 1, y) {
-            exits.push((idx + 1, 1.0))
+            exits.push((idx + 1, cost(idx + 1, 1.0)))
         };
         if self.is_exit_valid(x, y - 1) {
-            exits.push((idx - w, 1.0))
+            exits.push((idx - w, cost(idx - w, 1.0)))
         };
         if self.is_exit_valid(x, y + 1) {
-            exits.push((idx + w, 1.0))
+            exits.push((idx + w, cost(idx + w, 1.0)))
         };
 
         // Diagonals
         if self.is_exit_valid(x - 1, y - 1) {
-            exits.push(((idx - w) - 1, 1.45));
+            exits.push(((idx - w) - 1, cost((idx - w) - 1, 1.45)));
         }
         if self.is_exit_valid(x + 1, y - 1) {
-            exits.push(((idx - w) + 1, 1.45));
+            exits.push(((idx - w) + 1, cost((idx - w) + 1, 1.45)));
         }
         if self.is_exit_valid(x - 1, y + 1) {
-            exits.push(((idx + w) - 1, 1.45));
+            exits.push(((idx + w) - 1, cost((idx + w) - 1, 1.45)));
         }
         if self.is_exit_valid(x + 1, y + 1) {
-            exits.push(((idx + w) + 1, 1.45));
+            exits.push(((idx + w) + 1, cost((idx + w) + 1, 1.45)));
         }
 
         exits
@@ -200,50 +160,71 @@ impl Algorithm2D for Map {
     }
 }
 
-pub fn draw_map(ecs: &World, ctx: &mut Rltk) {
-    let map = ecs.fetch::<Map>();
-
-    let mut y = 0;
-    let mut x = 0;
-    for (idx, tile) in map.tiles.iter().enumerate() {
-        // Render a tile depending upon the tile type
-
-        if map.revealed_tiles[idx] {
-            let glyph;
-            let mut fg;
-            let mut bg = RGB::from_f32(0., 0., 0.);
-            match tile {
-                TileType::Floor => {
-                    glyph = rltk::to_cp437('.');
-                    fg = RGB::from_f32(0.0, 0.5, 0.5);
-                }
-                TileType::Wall => {
-                    glyph = wall_glyph(&*map, x, y);
-                    fg = RGB::from_f32(0., 1.0, 0.);
-                }
-                TileType::DownStairs => {
-                    glyph = rltk::to_cp437('>');
-                    fg = RGB::from_f32(0., 1.0, 1.0);
-                }
-            }
-
-            if map.bloodstains.contains(&idx) {
-                bg = RGB::from_f32(0.75, 0.0, 0.0);
-            }
-            if !map.visible_tiles[idx] {
-                fg = fg.to_greyscale();
-                bg = RGB::from_f32(0., 0., 0.);
-            }
-            ctx.set(x, y, fg, bg, glyph);
+/// Works out how a revealed tile looks. Tiles outside the player's view are
+/// greyed out.
+pub fn get_tile_glyph(idx: usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
+    let glyph;
+    let mut fg;
+    let mut bg = RGB::from_f32(0., 0., 0.);
+    match map.tiles[idx] {
+        TileType::Floor => {
+            glyph = rltk::to_cp437('.');
+            fg = RGB::from_f32(0.0, 0.5, 0.5);
         }
-
-        // Move the coordinates
-        x += 1;
-        if x > MAPWIDTH as i32 - 1 {
-            x = 0;
-            y += 1;
+        TileType::Wall => {
+            let x = idx as i32 % map.width;
+            let y = idx as i32 / map.width;
+            glyph = wall_glyph(map, x, y);
+            fg = RGB::from_f32(0., 1.0, 0.);
+        }
+        TileType::DownStairs => {
+            glyph = rltk::to_cp437('>');
+            fg = RGB::from_f32(0., 1.0, 1.0);
+        }
+        TileType::UpStairs => {
+            glyph = rltk::to_cp437('<');
+            fg = RGB::from_f32(0., 1.0, 1.0);
+        }
+        TileType::ClosedDoor => {
+            glyph = rltk::to_cp437('+');
+            fg = RGB::named(rltk::CHOCOLATE);
         }
+        TileType::OpenDoor => {
+            glyph = rltk::to_cp437('\'');
+            fg = RGB::named(rltk::CHOCOLATE);
+        }
+        TileType::ShallowWater => {
+            glyph = rltk::to_cp437('~');
+            fg = RGB::named(rltk::CYAN);
+        }
+        TileType::DeepWater => {
+            glyph = rltk::to_cp437('~');
+            fg = RGB::named(rltk::BLUE);
+        }
+        TileType::Lava => {
+            glyph = rltk::to_cp437('~');
+            fg = RGB::named(rltk::ORANGE);
+            bg = RGB::from_f32(0.4, 0.05, 0.0);
+        }
+        TileType::Chasm => {
+            glyph = rltk::to_cp437('░');
+            fg = RGB::named(rltk::DARKSLATEGRAY);
+        }
+        TileType::Bridge => {
+            glyph = rltk::to_cp437('=');
+            fg = RGB::named(rltk::BURLYWOOD);
+        }
+    }
+
+    if map.bloodstains.contains(&idx) {
+        bg = RGB::from_f32(0.75, 0.0, 0.0);
     }
+    if !map.visible_tiles[idx] {
+        fg = fg.to_greyscale();
+        bg = RGB::from_f32(0., 0., 0.);
+    }
+
+    (glyph, fg, bg)
 }
 
 fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
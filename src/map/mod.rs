@@ -1,8 +1,15 @@
+#[allow(clippy::module_inception)]
 pub mod map;
 pub use map::*;
 
 pub mod random_table;
-pub use random_table::*;
 
 pub mod spawner;
-pub use spawner::*;
+
+pub mod builders;
+
+pub mod seed;
+pub use seed::*;
+
+pub mod dungeon;
+pub use dungeon::*;
@@ -1,56 +1,262 @@
 use rltk::RandomNumberGenerator;
 
-pub struct RandomEntry {
-    name: String,
+/// One possible result of a roll. It is only available on depths from
+/// `min_depth` to `max_depth`, inclusive.
+#[derive(Clone, Debug)]
+pub struct RandomEntry<T> {
+    value: T,
     weight: i32,
+    min_depth: i32,
+    max_depth: i32,
 }
 
-impl RandomEntry {
-    pub fn new<S: ToString>(name: S, weight: i32) -> RandomEntry {
+impl<T> RandomEntry<T> {
+    pub fn new(value: T, weight: i32, min_depth: i32, max_depth: i32) -> RandomEntry<T> {
         RandomEntry {
-            name: name.to_string(),
+            value,
             weight,
+            min_depth,
+            max_depth,
         }
     }
+
+    fn available_on(&self, depth: i32) -> bool {
+        depth >= self.min_depth && depth <= self.max_depth
+    }
 }
 
-#[derive(Default)]
-pub struct RandomTable {
-    entries: Vec<RandomEntry>,
-    total_weight: i32,
+/// A weighted table of results. The chance of an entry coming up is its
+/// weight divided by the total weight of everything available on that depth.
+#[derive(Clone, Debug)]
+pub struct RandomTable<T> {
+    entries: Vec<RandomEntry<T>>,
 }
 
-impl RandomTable {
-    pub fn new() -> RandomTable {
+impl<T> Default for RandomTable<T> {
+    fn default() -> Self {
         RandomTable {
             entries: Vec::new(),
-            total_weight: 0,
         }
     }
+}
 
-    pub fn add<S: ToString>(mut self, name: S, weight: i32) -> RandomTable {
-        self.total_weight += weight;
-        self.entries
-            .push(RandomEntry::new(name.to_string(), weight));
+impl<T: Clone> RandomTable<T> {
+    pub fn new() -> RandomTable<T> {
+        RandomTable::default()
+    }
+
+    /// Adds an entry that can turn up on any depth.
+    pub fn add(self, value: T, weight: i32) -> RandomTable<T> {
+        self.add_for_depths(value, weight, i32::MIN, i32::MAX)
+    }
+
+    /// Adds an entry that can only turn up between `min_depth` and
+    /// `max_depth`. Entries that could never be rolled, because their weight
+    /// isn't positive or the depth range is empty, are dropped.
+    pub fn add_for_depths(
+        mut self,
+        value: T,
+        weight: i32,
+        min_depth: i32,
+        max_depth: i32,
+    ) -> RandomTable<T> {
+        if weight > 0 && min_depth <= max_depth {
+            self.entries
+                .push(RandomEntry::new(value, weight, min_depth, max_depth));
+        }
         self
     }
 
-    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> String {
-        if self.total_weight == 0 {
-            return "None".to_string();
+    /// Picks one result for the given depth, or `None` if nothing is
+    /// available there.
+    pub fn roll(&self, rng: &mut RandomNumberGenerator, depth: i32) -> Option<T> {
+        let available: Vec<&RandomEntry<T>> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.available_on(depth))
+            .collect();
+        Self::pick(rng, &available).map(|index| available[index].value.clone())
+    }
+
+    /// Picks up to `count` different entries for the given depth. Once an
+    /// entry has come up it is taken out of the running, so fewer results
+    /// come back if the table runs dry.
+    pub fn roll_unique(&self, rng: &mut RandomNumberGenerator, depth: i32, count: usize) -> Vec<T> {
+        let mut available: Vec<&RandomEntry<T>> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.available_on(depth))
+            .collect();
+
+        let mut results = Vec::new();
+        while results.len() < count {
+            match Self::pick(rng, &available) {
+                Some(index) => results.push(available.remove(index).value.clone()),
+                None => break,
+            }
         }
-        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
-        let mut index: usize = 0;
+        results
+    }
+
+    /// The combined weight of everything available on the given depth.
+    pub fn total_weight(&self, depth: i32) -> i32 {
+        self.entries
+            .iter()
+            .filter(|entry| entry.available_on(depth))
+            .map(|entry| entry.weight)
+            .sum()
+    }
+
+    /// Returns the index of a weighted pick from `entries`.
+    fn pick(rng: &mut RandomNumberGenerator, entries: &[&RandomEntry<T>]) -> Option<usize> {
+        let total_weight: i32 = entries.iter().map(|entry| entry.weight).sum();
+        if total_weight <= 0 {
+            return None;
+        }
+
+        let mut roll = rng.roll_dice(1, total_weight) - 1;
+        for (index, entry) in entries.iter().enumerate() {
+            if roll < entry.weight {
+                return Some(index);
+            }
+            roll -= entry.weight;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const ROLLS: i32 = 100_000;
 
-        while roll > 0 {
-            if roll < self.entries[index].weight {
-                return self.entries[index].name.clone();
+    fn count_rolls(table: &RandomTable<&'static str>, depth: i32) -> HashMap<&'static str, i32> {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let mut counts = HashMap::new();
+        for _ in 0..ROLLS {
+            if let Some(value) = table.roll(&mut rng, depth) {
+                *counts.entry(value).or_insert(0) += 1;
             }
+        }
+        counts
+    }
+
+    fn assert_frequency(counts: &HashMap<&str, i32>, value: &str, expected: f32) {
+        let actual = *counts.get(value).unwrap_or(&0) as f32 / ROLLS as f32;
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "{} came up {:.3} of the time, expected {:.3}",
+            value,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn frequencies_match_weights() {
+        let table = RandomTable::new()
+            .add("common", 6)
+            .add("uncommon", 3)
+            .add("rare", 1);
+        let counts = count_rolls(&table, 1);
+
+        assert_eq!(counts.values().sum::<i32>(), ROLLS);
+        assert_frequency(&counts, "common", 0.6);
+        assert_frequency(&counts, "uncommon", 0.3);
+        assert_frequency(&counts, "rare", 0.1);
+    }
+
+    #[test]
+    fn first_entry_can_be_rolled() {
+        let table = RandomTable::new().add("only", 1);
+        let mut rng = RandomNumberGenerator::seeded(7);
+        for _ in 0..100 {
+            assert_eq!(table.roll(&mut rng, 1), Some("only"));
+        }
+    }
+
+    #[test]
+    fn non_positive_weights_are_dropped() {
+        let table = RandomTable::new()
+            .add("zero", 0)
+            .add("negative", -3)
+            .add("kept", 2);
+        let counts = count_rolls(&table, 1);
+
+        assert_eq!(counts.get("zero"), None);
+        assert_eq!(counts.get("negative"), None);
+        assert_eq!(counts.get("kept"), Some(&ROLLS));
+    }
+
+    #[test]
+    fn empty_table_rolls_nothing() {
+        let table: RandomTable<&str> = RandomTable::new().add("nothing", 0);
+        let mut rng = RandomNumberGenerator::seeded(1);
 
-            roll -= self.entries[index].weight;
-            index += 1;
+        assert_eq!(table.roll(&mut rng, 1), None);
+        assert!(table.roll_unique(&mut rng, 1, 3).is_empty());
+    }
+
+    #[test]
+    fn depth_bounds_are_respected() {
+        let table = RandomTable::new()
+            .add("anywhere", 1)
+            .add_for_depths("shallow", 1, 1, 3)
+            .add_for_depths("deep", 1, 4, 10);
+
+        let shallow = count_rolls(&table, 2);
+        assert_eq!(shallow.get("deep"), None);
+        assert_frequency(&shallow, "anywhere", 0.5);
+        assert_frequency(&shallow, "shallow", 0.5);
+
+        let deep = count_rolls(&table, 4);
+        assert_eq!(deep.get("shallow"), None);
+        assert_frequency(&deep, "deep", 0.5);
+
+        let deeper = count_rolls(&table, 11);
+        assert_eq!(deeper.get("anywhere"), Some(&ROLLS));
+    }
+
+    #[test]
+    fn roll_unique_never_repeats() {
+        let table = RandomTable::new()
+            .add("a", 10)
+            .add("b", 1)
+            .add("c", 1)
+            .add_for_depths("d", 1, 5, 5);
+        let mut rng = RandomNumberGenerator::seeded(3);
+
+        for _ in 0..1_000 {
+            let mut results = table.roll_unique(&mut rng, 1, 2);
+            assert_eq!(results.len(), 2);
+            results.dedup();
+            assert_eq!(results.len(), 2);
+        }
+
+        let mut everything = table.roll_unique(&mut rng, 1, 10);
+        everything.sort();
+        assert_eq!(everything, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn roll_unique_follows_weights_for_first_pick() {
+        let table = RandomTable::new().add("heavy", 3).add("light", 1);
+        let mut rng = RandomNumberGenerator::seeded(11);
+        let mut heavy_first = 0;
+        for _ in 0..ROLLS {
+            if table.roll_unique(&mut rng, 1, 2)[0] == "heavy" {
+                heavy_first += 1;
+            }
         }
 
-        "None".to_string()
+        let frequency = heavy_first as f32 / ROLLS as f32;
+        assert!(
+            (frequency - 0.75).abs() < 0.01,
+            "heavy first {:.3}",
+            frequency
+        );
     }
 }
@@ -0,0 +1,50 @@
+use rltk::RandomNumberGenerator;
+
+/// The master seed of a run. Every level derives its own random number
+/// generator from it, so a seed and a depth are enough to rebuild a level and
+/// its inhabitants exactly.
+#[derive(PartialEq, Copy, Clone)]
+pub struct MasterSeed {
+    pub seed: u64,
+}
+
+impl MasterSeed {
+    pub fn new(seed: u64) -> MasterSeed {
+        MasterSeed { seed }
+    }
+
+    pub fn random() -> MasterSeed {
+        MasterSeed::new(RandomNumberGenerator::new().roll_dice(1, 999_999) as u64)
+    }
+
+    /// Reads `--seed <n>` or `--seed=<n>` from the command line.
+    pub fn from_args() -> Option<MasterSeed> {
+        let args: Vec<String> = std::env::args().collect();
+        for (i, arg) in args.iter().enumerate() {
+            let value = if arg == "--seed" {
+                args.get(i + 1).map(|v| v.as_str())
+            } else {
+                arg.strip_prefix("--seed=")
+            };
+            if let Some(seed) = value.and_then(|v| v.parse::<u64>().ok()) {
+                return Some(MasterSeed::new(seed));
+            }
+        }
+        None
+    }
+
+    /// Returns the generator used to build the level at the given depth.
+    pub fn rng_for_depth(&self, depth: i32) -> RandomNumberGenerator {
+        RandomNumberGenerator::seeded(self.seed_for_depth(depth))
+    }
+
+    fn seed_for_depth(&self, depth: i32) -> u64 {
+        // splitmix64, so that neighbouring seeds and depths don't produce similar levels
+        let mut z = self
+            .seed
+            .wrapping_add((depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
@@ -1,16 +1,27 @@
-use crate::components::{DefensePowerBonus, EquipmentSlot, Equippable, MeleePowerBonus};
 use crate::{
-    map::MAPWIDTH, random_table::RandomTable, AreaOfEffect, BlocksTile, CombatStats, Confusion,
-    Consumable, InflictsDamage, Item, Monster, Name, Player, Position, ProvidesHealing, Ranged,
-    Renderable, SerializeMe, Viewshed,
+    attributes::max_hp,
+    map::{Map, MasterDungeonMap, TileType},
+    random_table::RandomTable,
+    raws::{
+        get_healing_amount, get_healing_items, get_pack_size, get_spawn_cost,
+        get_spawn_table_for_depth, get_uniques_for_depth, spawn_named_entity, SpawnType, RAWS,
+    },
+    Attributes, CombatStats, Experience, Name, Player, Position, Renderable, SerializeMe, Viewshed,
 };
-use rltk::{RandomNumberGenerator, Rect, RGB};
+use rltk::{Point, RandomNumberGenerator, Rect, RGB};
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use std::collections::HashMap;
 
 /// Spawns the player and returns his/her entity object.
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
+    let attributes = Attributes {
+        might: 14,
+        fitness: 12,
+        quickness: 12,
+        intelligence: 12,
+    };
+    let player_hp = max_hp(30, attributes.fitness);
+
     ecs.create_entity()
         .with(Position {
             x: player_x,
@@ -32,273 +43,262 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
             name: "Player".to_string(),
         })
         .with(CombatStats {
-            max_hp: 30,
-            hp: 30,
+            max_hp: player_hp,
+            hp: player_hp,
             defense: 2,
             power: 5,
         })
+        .with(attributes)
+        .with(Experience {
+            level: 1,
+            xp: 0,
+            unspent_level_ups: 0,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build()
 }
 
-const MAX_MONSTERS: i32 = 4;
+/// Every level gets `BASE_BUDGET + depth * BUDGET_PER_DEPTH` to spend on
+/// monsters, items and traps, each of which costs what the raws say.
+const BASE_BUDGET: i32 = 30;
+const BUDGET_PER_DEPTH: i32 = 10;
+/// How often the spawner may roll something it can't afford before it calls
+/// the level done.
+const MAX_FAILED_ROLLS: i32 = 20;
+/// Nothing spawns this close to the start.
+const QUIET_RADIUS: f32 = 6.0;
+/// The least healing, in hit points, every level has lying around.
+pub const MIN_HEALING: i32 = 16;
 
-fn room_table(map_depth: i32) -> RandomTable {
-    RandomTable::new()
-        .add("Goblin", 10)
-        .add("Orc", 1 + map_depth)
-        .add("Health Potion", 7)
-        .add("Fireball Scroll", 2 + map_depth)
-        .add("Confusion Scroll", 2 + map_depth)
-        .add("Magic Missile Scroll", 4)
-        .add("Dagger", 3)
-        .add("Shield", 3)
-        .add("Longsword", map_depth - 1)
-        .add("Tower Shield", map_depth - 1)
+fn room_table(map_depth: i32) -> RandomTable<String> {
+    get_spawn_table_for_depth(&RAWS.lock().unwrap(), map_depth)
 }
 
-#[allow(clippy::map_entry)]
-pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
-    let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
-
-    {
-        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-        let num_spawns = rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3;
-
-        for _i in 0..num_spawns {
-            let mut added = false;
-            let mut tries = 0;
-            while !added && tries < 20 {
-                let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
-                let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
-                let idx = (y * MAPWIDTH) + x;
-                if !spawn_points.contains_key(&idx) {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
-                    added = true;
-                } else {
-                    tries += 1;
-                }
+/// Lists the floor tiles inside a room.
+pub fn room_tiles(map: &Map, room: &Rect) -> Vec<usize> {
+    let mut tiles: Vec<usize> = Vec::new();
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::Floor {
+                tiles.push(idx);
             }
         }
     }
+    tiles
+}
 
-    for spawn in spawn_points.iter() {
-        let x = (*spawn.0 % MAPWIDTH) as i32;
-        let y = (*spawn.0 / MAPWIDTH) as i32;
+/// Fills a level with stuff! `areas` are the rooms or regions of the map,
+/// given as lists of tile indices. The level's budget is spent one roll at a
+/// time, each in a random area, until it runs out. The area the player starts
+/// in, and everything close to the start, is left alone. Packs keep the tiles
+/// around their leader to themselves.
+pub fn spawn_level(
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+    areas: &[Vec<usize>],
+    start: &Position,
+    map_depth: i32,
+    spawn_list: &mut Vec<(usize, String)>,
+) {
+    let spawn_table = room_table(map_depth);
+    let start_idx = map.xy_idx(start.x, start.y);
+    let start_point = Point::new(start.x, start.y);
+    let mut areas: Vec<Vec<usize>> = areas
+        .iter()
+        .filter(|area| !area.contains(&start_idx))
+        .map(|area| {
+            area.iter()
+                .filter(|idx| {
+                    let pos = Point::new(**idx as i32 % map.width, **idx as i32 / map.width);
+                    rltk::DistanceAlg::Pythagoras.distance2d(start_point, pos) > QUIET_RADIUS
+                })
+                .copied()
+                .collect::<Vec<usize>>()
+        })
+        .filter(|area| !area.is_empty())
+        .collect();
+
+    let mut budget = BASE_BUDGET + map_depth * BUDGET_PER_DEPTH;
+    let mut failed_rolls = 0;
+    while budget > 0 && !areas.is_empty() && failed_rolls < MAX_FAILED_ROLLS {
+        let Some(spawn) = spawn_table.roll(rng, map_depth) else {
+            break;
+        };
+        let cost = get_spawn_cost(&RAWS.lock().unwrap(), &spawn);
+        if cost > budget {
+            failed_rolls += 1;
+            continue;
+        }
+        budget -= cost;
+
+        let area_index = (rng.roll_dice(1, areas.len() as i32) - 1) as usize;
+        let area = &mut areas[area_index];
+        let tile_index = (rng.roll_dice(1, area.len() as i32) - 1) as usize;
+        let map_idx = area.remove(tile_index);
+        if let Some(followers) = get_pack_size(&RAWS.lock().unwrap(), &spawn) {
+            reserve_nearest(map, map_idx, followers, area);
+        }
+        spawn_list.push((map_idx, spawn));
 
-        match spawn.1.as_ref() {
-            "Goblin" => goblin(ecs, x, y),
-            "Orc" => orc(ecs, x, y),
-            "Health Potion" => health_potion(ecs, x, y),
-            "Fireball Scroll" => fireball_scroll(ecs, x, y),
-            "Confusion Scroll" => confusion_scroll(ecs, x, y),
-            "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
-            "Dagger" => dagger(ecs, x, y),
-            "Shield" => shield(ecs, x, y),
-            "Longsword" => longsword(ecs, x, y),
-            "Tower Shield" => tower_shield(ecs, x, y),
-            _ => {}
+        if area.is_empty() {
+            areas.remove(area_index);
         }
     }
 }
 
-fn orc(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('o'), "Orc");
-}
-fn goblin(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin");
-}
+/// Tops up a finished level's spawns with healing items until it has at least
+/// `MIN_HEALING` hit points worth of them. They go on free floor tiles the
+/// player can reach, away from the start.
+pub fn guarantee_healing(
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+    start: &Position,
+    spawn_list: &mut Vec<(usize, String)>,
+) {
+    let raws = RAWS.lock().unwrap();
+    let healing_items = get_healing_items(&raws);
+    if healing_items.is_empty() {
+        return;
+    }
 
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharType, name: S) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph,
-            fg: RGB::named(rltk::RED),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 1,
-        })
-        .with(Viewshed {
-            visible_tiles: Vec::new(),
-            range: 8,
-            dirty: true,
-        })
-        .with(Monster {})
-        .with(Name {
-            name: name.to_string(),
-        })
-        .with(BlocksTile {})
-        .with(CombatStats {
-            max_hp: 16,
-            hp: 16,
-            defense: 1,
-            power: 4,
-        })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
+    let mut healing: i32 = spawn_list
+        .iter()
+        .map(|(_idx, name)| get_healing_amount(&raws, name))
+        .sum();
+    if healing >= MIN_HEALING {
+        return;
+    }
 
-fn health_potion(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437('¡'),
-            fg: RGB::named(rltk::MAGENTA),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Health Potion".to_string(),
-        })
-        .with(Item {})
-        .with(Consumable {})
-        .with(ProvidesHealing { heal_amount: 8 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
+    let mut walkable = map.clone();
+    walkable.populate_blocked();
+    let distances = rltk::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &[map.xy_idx(start.x, start.y)],
+        &walkable,
+        (map.width * map.height) as f32,
+    )
+    .map;
 
-fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::CYAN),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Magic Missile Scroll".to_string(),
+    let start_point = Point::new(start.x, start.y);
+    let mut free_tiles: Vec<usize> = map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(idx, tile)| {
+            let pos = Point::new(*idx as i32 % map.width, *idx as i32 / map.width);
+            **tile == TileType::Floor
+                && distances[*idx] != f32::MAX
+                && rltk::DistanceAlg::Pythagoras.distance2d(start_point, pos) > QUIET_RADIUS
+                && !spawn_list.iter().any(|(spawn_idx, _name)| spawn_idx == idx)
         })
-        .with(Item {})
-        .with(Consumable {})
-        .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 20 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
+        .map(|(idx, _tile)| idx)
+        .collect();
 
-fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::ORANGE),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Fireball Scroll".to_string(),
-        })
-        .with(Item {})
-        .with(Consumable {})
-        .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 20 })
-        .with(AreaOfEffect { radius: 3 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+    while healing < MIN_HEALING && !free_tiles.is_empty() {
+        let Some(item) = rng.random_slice_entry(&healing_items).cloned() else {
+            break;
+        };
+        let tile_index = (rng.roll_dice(1, free_tiles.len() as i32) - 1) as usize;
+        healing += get_healing_amount(&raws, &item).max(1);
+        spawn_list.push((free_tiles.remove(tile_index), item));
+    }
 }
 
-fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::PINK),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Confusion Scroll".to_string(),
-        })
-        .with(Item {})
-        .with(Consumable {})
-        .with(Ranged { range: 6 })
-        .with(Confusion { turns: 4 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+/// Takes the `count` tiles closest to `center` out of the running.
+fn reserve_nearest(map: &Map, center: usize, count: usize, areas: &mut Vec<usize>) {
+    let center = Point::new(center as i32 % map.width, center as i32 / map.width);
+    areas.sort_by_key(|idx| {
+        let pos = Point::new(*idx as i32 % map.width, *idx as i32 / map.width);
+        (pos.x - center.x).abs().max((pos.y - center.y).abs())
+    });
+    areas.drain(..count.min(areas.len()));
 }
 
-fn dagger(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437('/'),
-            fg: RGB::named(rltk::CYAN),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Dagger".to_string(),
-        })
-        .with(Item {})
-        .with(Equippable {
-            slot: EquipmentSlot::Melee,
-        })
-        .with(MeleePowerBonus { power: 2 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+/// Tells whether a spawn list entry is a whole pack rather than one entity.
+pub fn is_pack(name: &str) -> bool {
+    get_pack_size(&RAWS.lock().unwrap(), name).is_some()
 }
 
-fn shield(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437('('),
-            fg: RGB::named(rltk::CYAN),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Shield".to_string(),
-        })
-        .with(Item {})
-        .with(Equippable {
-            slot: EquipmentSlot::Shield,
-        })
-        .with(DefensePowerBonus { defense: 1 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+/// Spawns a named entity (name in tuple.1) at the location in (tuple.0)
+pub fn spawn_entity(ecs: &mut World, spawn: &(usize, String)) {
+    let map = ecs.fetch::<Map>();
+    let x = (spawn.0 % map.width as usize) as i32;
+    let y = (spawn.0 / map.width as usize) as i32;
+    std::mem::drop(map);
+
+    if spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        ecs,
+        &spawn.1,
+        SpawnType::AtPosition { x, y },
+    )
+    .is_none()
+    {
+        rltk::console::log(format!(
+            "WARNING: We don't know how to spawn [{}]!",
+            spawn.1
+        ));
+    }
 }
 
-fn longsword(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437('/'),
-            fg: RGB::named(rltk::YELLOW),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Longsword".to_string(),
-        })
-        .with(Item {})
-        .with(Equippable {
-            slot: EquipmentSlot::Melee,
-        })
-        .with(MeleePowerBonus { power: 4 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+/// Places the unique monsters that belong on the current level, unless they
+/// have been killed already. Each one waits on the closest free floor tile to
+/// the way down.
+pub fn spawn_uniques(ecs: &mut World) {
+    let depth = ecs.fetch::<Map>().depth;
+    let uniques: Vec<String> = get_uniques_for_depth(&RAWS.lock().unwrap(), depth)
+        .into_iter()
+        .filter(|name| !ecs.fetch::<MasterDungeonMap>().is_unique_slain(name))
+        .collect();
+
+    for name in uniques {
+        let Some(lair) = find_lair(ecs) else {
+            rltk::console::log(format!("WARNING: Nowhere to put [{}]!", name));
+            continue;
+        };
+        spawn_named_entity(
+            &RAWS.lock().unwrap(),
+            ecs,
+            &name,
+            SpawnType::AtPosition {
+                x: lair.x,
+                y: lair.y,
+            },
+        );
+    }
 }
 
-fn tower_shield(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position { x, y })
-        .with(Renderable {
-            glyph: rltk::to_cp437('('),
-            fg: RGB::named(rltk::YELLOW),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name {
-            name: "Tower Shield".to_string(),
-        })
-        .with(Item {})
-        .with(Equippable {
-            slot: EquipmentSlot::Shield,
+/// Finds the unoccupied floor tile closest to the down stairs, on foot.
+fn find_lair(ecs: &World) -> Option<Point> {
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let occupied: Vec<usize> = positions
+        .join()
+        .map(|pos| map.xy_idx(pos.x, pos.y))
+        .collect();
+
+    let stairs_idx = map
+        .tiles
+        .iter()
+        .position(|tile| *tile == TileType::DownStairs)?;
+    let dijkstra_map = rltk::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &[stairs_idx],
+        &*map,
+        (map.width * map.height) as f32,
+    );
+
+    map.tiles
+        .iter()
+        .enumerate()
+        .filter(|(idx, tile)| {
+            **tile == TileType::Floor
+                && !occupied.contains(idx)
+                && dijkstra_map.map[*idx] < f32::MAX
         })
-        .with(DefensePowerBonus { defense: 3 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .min_by(|(a, _), (b, _)| dijkstra_map.map[*a].total_cmp(&dijkstra_map.map[*b]))
+        .map(|(idx, _tile)| Point::new(idx as i32 % map.width, idx as i32 / map.width))
 }
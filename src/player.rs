@@ -1,21 +1,28 @@
-use crate::ui::gamelog::GameLog;
+use crate::{
+    attributes::fitness_hp,
+    ui::{gamelog::GameLog, gui::LevelUpBonus},
+};
 
 use super::{
-    CombatStats, Item, Map, Monster, Player, Position, RunState, State, TileType, Viewshed,
-    WantsToMelee, WantsToPickupItem,
+    Ammunition, Attributes, CombatStats, Confusion, EntityMoved, Equipped, Experience, Hidden,
+    InBackpack, Item, Map, Monster, Name, Player, Position, RangedWeapon, RunState, State,
+    TileType, Viewshed, WantsToMelee, WantsToPickupItem,
 };
-use rltk::{Point, Rltk, VirtualKeyCode};
+use rltk::{Point, RandomNumberGenerator, Rltk, VirtualKeyCode};
 use specs::prelude::*;
-use std::cmp::{max, min};
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    let (delta_x, delta_y) = stagger_if_confused(delta_x, delta_y, ecs);
+
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.read_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
     let entities = ecs.entities();
     let combat_stats = ecs.read_storage::<CombatStats>();
-    let map = ecs.fetch::<Map>();
+    let mut map = ecs.fetch_mut::<Map>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let mut entity_moved = ecs.write_storage::<EntityMoved>();
+    let mut door_opened = false;
 
     for (entity, _player, pos, viewshed) in
         (&entities, &players, &mut positions, &mut viewsheds).join()
@@ -44,9 +51,16 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             }
         }
 
-        if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+        if map.tiles[destination_idx] == TileType::ClosedDoor {
+            map.tiles[destination_idx] = TileType::OpenDoor;
+            door_opened = true;
+        } else if !map.blocked[destination_idx] {
+            pos.x = (pos.x + delta_x).clamp(0, map.width - 1);
+            pos.y = (pos.y + delta_y).clamp(0, map.height - 1);
+
+            entity_moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert marker");
 
             viewshed.dirty = true;
             let mut ppos = ecs.write_resource::<Point>();
@@ -54,6 +68,85 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             ppos.y = pos.y;
         }
     }
+
+    if door_opened {
+        // Everybody might be able to see through the doorway now
+        for viewshed in (&mut viewsheds).join() {
+            viewshed.dirty = true;
+        }
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.entries.push("You open the door.".to_string());
+    }
+}
+
+/// A confused player stumbles off in a random direction instead of the one
+/// they picked, until the confusion wears off.
+fn stagger_if_confused(delta_x: i32, delta_y: i32, ecs: &mut World) -> (i32, i32) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut confusion = ecs.write_storage::<Confusion>();
+    match confusion.get_mut(*player_entity) {
+        None => (delta_x, delta_y),
+        Some(confused) => {
+            confused.turns -= 1;
+            if confused.turns < 1 {
+                confusion.remove(*player_entity);
+            }
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog
+                .entries
+                .push("You stumble around in confusion.".to_string());
+            let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+            (rng.range(-1, 2), rng.range(-1, 2))
+        }
+    }
+}
+
+/// Looks around carefully for traps, which takes a turn. Anything hidden close
+/// by is likely to be found.
+fn search(ecs: &mut World) -> RunState {
+    const SEARCH_RADIUS: f32 = 3.0;
+
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let map = ecs.fetch::<Map>();
+    let names = ecs.read_storage::<Name>();
+    let mut hidden = ecs.write_storage::<Hidden>();
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let mut found_something = false;
+    if let Some(viewshed) = viewsheds.get(*player_entity) {
+        for tile in viewshed.visible_tiles.iter() {
+            if rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *tile) > SEARCH_RADIUS {
+                continue;
+            }
+            let idx = map.xy_idx(tile.x, tile.y);
+            for entity in map.tile_content[idx].iter() {
+                if hidden.get(*entity).is_some() && rng.roll_dice(1, 6) > 1 {
+                    hidden.remove(*entity);
+                    found_something = true;
+                    if let Some(name) = names.get(*entity) {
+                        gamelog.entries.push(format!("You found a {}.", &name.name));
+                    }
+                }
+            }
+        }
+    }
+    if !found_something {
+        gamelog
+            .entries
+            .push("You search the area but find nothing.".to_string());
+    }
+
+    RunState::PlayerTurn
+}
+
+pub fn try_previous_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+    map.tiles[player_idx] == TileType::UpStairs
 }
 
 pub fn try_next_level(ecs: &mut World) -> bool {
@@ -105,6 +198,38 @@ fn get_item(ecs: &mut World) {
     }
 }
 
+/// Starts aiming the player's bow, if they have one ready and something to
+/// shoot with it.
+fn aim_ranged_weapon(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let Some((weapon, _equipped)) = (&ranged_weapons, &equipped)
+        .join()
+        .find(|(_weapon, equipped_by)| equipped_by.owner == *player_entity)
+    else {
+        gamelog
+            .entries
+            .push("You have nothing to shoot with.".to_string());
+        return RunState::AwaitingInput;
+    };
+    if !(&ammunition, &backpack)
+        .join()
+        .any(|(_ammo, carried)| carried.owner == *player_entity)
+    {
+        gamelog.entries.push("You are out of arrows.".to_string());
+        return RunState::AwaitingInput;
+    }
+
+    RunState::ShowRangedTargeting {
+        range: weapon.range,
+    }
+}
+
 fn skip_turn(ecs: &mut World) -> RunState {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
@@ -172,16 +297,27 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
 
             // Level changes
             VirtualKeyCode::Y => {
+                if try_previous_level(&mut gs.ecs) {
+                    return RunState::PreviousLevel;
+                }
                 if try_next_level(&mut gs.ecs) {
                     return RunState::NextLevel;
                 }
             }
 
+            // Look for traps
+            VirtualKeyCode::S => return search(&mut gs.ecs),
+
             // Picking up items
             VirtualKeyCode::G => get_item(&mut gs.ecs),
             VirtualKeyCode::I => return RunState::ShowInventory,
             VirtualKeyCode::D => return RunState::ShowDropItem,
+            VirtualKeyCode::T => return RunState::ShowThrowItem,
             VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            VirtualKeyCode::C => return RunState::ShowCharacterSheet,
+
+            // Shooting
+            VirtualKeyCode::F => return aim_ranged_weapon(&mut gs.ecs),
 
             // Save and Quit
             VirtualKeyCode::Escape => return RunState::SaveGame,
@@ -189,5 +325,58 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             _ => return RunState::AwaitingInput,
         },
     }
+
+    if over_chasm(&gs.ecs) {
+        return RunState::FallThroughChasm;
+    }
     RunState::PlayerTurn
 }
+
+fn over_chasm(ecs: &World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+    map.tiles[player_idx] == TileType::Chasm
+}
+
+/// How much the attribute picked on a level up goes up by.
+pub const LEVEL_UP_ATTRIBUTE_BONUS: i32 = 2;
+
+/// Spends one of the player's level ups on `bonus`. Returns whether there
+/// are any left to pick.
+pub fn apply_level_up_bonus(ecs: &mut World, bonus: LevelUpBonus) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let mut combat_stats = ecs.write_storage::<CombatStats>();
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut log = ecs.write_resource::<GameLog>();
+
+    if let Some(attributes) = attributes.get_mut(player_entity) {
+        let (name, attribute) = match bonus {
+            LevelUpBonus::Might => ("stronger", &mut attributes.might),
+            LevelUpBonus::Fitness => ("tougher", &mut attributes.fitness),
+            LevelUpBonus::Quickness => ("quicker", &mut attributes.quickness),
+            LevelUpBonus::Intelligence => ("sharper", &mut attributes.intelligence),
+        };
+        let before = *attribute;
+        *attribute += LEVEL_UP_ATTRIBUTE_BONUS;
+        log.entries.push(format!("You feel {}.", name));
+
+        // Fitness counts towards hit points straight away
+        if bonus == LevelUpBonus::Fitness {
+            if let Some(stats) = combat_stats.get_mut(player_entity) {
+                let gained = fitness_hp(attributes.fitness) - fitness_hp(before);
+                stats.max_hp += gained;
+                stats.hp += gained;
+            }
+        }
+    }
+
+    match experience.get_mut(player_entity) {
+        Some(experience) => {
+            experience.unspent_level_ups = i32::max(0, experience.unspent_level_ups - 1);
+            experience.unspent_level_ups > 0
+        }
+        None => false,
+    }
+}
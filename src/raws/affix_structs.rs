@@ -0,0 +1,26 @@
+use serde::Deserialize;
+
+/// A magic property that can be rolled onto a piece of gear, like "Flaming"
+/// or "of the Bear". Every bonus is optional and adds to what the base item
+/// already has.
+#[derive(Deserialize, Debug)]
+pub struct RawAffix {
+    pub name: String,
+    /// "prefix" goes before the item's name, "suffix" after it.
+    pub kind: String,
+    /// Only gear for this slot can roll it; any slot if left out.
+    pub slot: Option<String>,
+    pub weight: i32,
+    pub min_depth: Option<i32>,
+    pub power_bonus: Option<i32>,
+    pub defense_bonus: Option<i32>,
+    pub bonus_damage: Option<RawBonusDamage>,
+    /// Percentage of the damage dealt that heals the wielder.
+    pub life_steal: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawBonusDamage {
+    pub damage_type: String,
+    pub damage: i32,
+}
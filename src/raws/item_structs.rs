@@ -0,0 +1,69 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct RawItem {
+    pub name: String,
+    pub renderable: Option<RawRenderable>,
+    pub consumable: Option<RawConsumable>,
+    pub equippable: Option<RawEquippable>,
+    /// Items of this kind ("potion", "scroll") start out under a random
+    /// disguise each run, until the player identifies them.
+    pub unidentified: Option<String>,
+    /// What spawning one takes out of a level's budget.
+    #[serde(default)]
+    pub cost: i32,
+    /// How much of a creature's carrying capacity it takes up.
+    #[serde(default)]
+    pub weight: i32,
+    /// Makes it a stack of this many arrows.
+    pub ammunition: Option<i32>,
+    /// Dice rolled for damage when it's thrown at somebody, like "1d4".
+    pub thrown_damage: Option<String>,
+    /// Breaks when thrown, splashing its effect over this radius.
+    pub shatters: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawRenderable {
+    pub glyph: String,
+    pub fg: String,
+    pub bg: String,
+    pub order: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawConsumable {
+    pub effects: RawEffects,
+}
+
+/// What happens when an item is used or a trap goes off. Every field is
+/// optional, so a definition only lists the effects it actually has.
+#[derive(Deserialize, Debug, Default)]
+pub struct RawEffects {
+    pub provides_healing: Option<i32>,
+    pub ranged: Option<i32>,
+    pub damage: Option<i32>,
+    /// What kind of damage it does: "fire", "cold", "poison" or "magic".
+    /// Physical if left out.
+    pub damage_type: Option<String>,
+    pub area_of_effect: Option<i32>,
+    pub confusion: Option<i32>,
+    #[serde(default)]
+    pub teleports: bool,
+    pub alarm: Option<i32>,
+    #[serde(default)]
+    pub identify: bool,
+    #[serde(default)]
+    pub remove_curse: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawEquippable {
+    pub slot: String,
+    /// How far a ranged weapon shoots.
+    pub range: Option<i32>,
+    /// Dice rolled for damage when hitting with it, like "1d8+2".
+    pub damage: Option<String>,
+    pub power_bonus: Option<i32>,
+    pub defense_bonus: Option<i32>,
+}
@@ -0,0 +1,14 @@
+use super::SpawnTableEntry;
+use serde::Deserialize;
+
+/// What a monster might leave behind when it dies. The table is rolled
+/// `rolls` times without repeats; `nothing` is the weight of a roll coming up
+/// empty.
+#[derive(Deserialize, Debug)]
+pub struct RawLootTable {
+    pub name: String,
+    pub drops: Vec<SpawnTableEntry>,
+    #[serde(default)]
+    pub nothing: i32,
+    pub rolls: Option<usize>,
+}
@@ -0,0 +1,86 @@
+use super::RawRenderable;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct RawMob {
+    pub name: String,
+    pub renderable: Option<RawRenderable>,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    pub stats: RawMobStats,
+    #[serde(default)]
+    pub attributes: RawAttributes,
+    #[serde(default)]
+    pub stats_per_depth: RawStatScaling,
+    pub vision_range: i32,
+    #[serde(default)]
+    pub cost: i32,
+    /// Experience for killing it; worked out from its cost if left out.
+    pub xp: Option<i32>,
+    /// Items the monster spawns wielding or wearing.
+    #[serde(default)]
+    pub equipped: Vec<String>,
+    /// Items the monster spawns carrying in its backpack.
+    #[serde(default)]
+    pub carried: Vec<String>,
+    /// Loot table rolled when the monster dies, on top of what it carries.
+    pub loot_table: Option<String>,
+    pub unique: Option<RawUnique>,
+    /// Damage types that only do it half harm.
+    #[serde(default)]
+    pub resistances: Vec<String>,
+    /// Damage types that do it no harm at all.
+    #[serde(default)]
+    pub immunities: Vec<String>,
+    /// Damage types that do it double harm.
+    #[serde(default)]
+    pub vulnerabilities: Vec<String>,
+}
+
+/// Marks a boss: it only ever appears on `depth`, and only once per run.
+#[derive(Deserialize, Debug)]
+pub struct RawUnique {
+    pub depth: i32,
+    pub death_message: String,
+}
+
+/// A monster's core attributes. Anything left out is an average 10.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct RawAttributes {
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+}
+
+impl Default for RawAttributes {
+    fn default() -> Self {
+        RawAttributes {
+            might: 10,
+            fitness: 10,
+            quickness: 10,
+            intelligence: 10,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawMobStats {
+    pub max_hp: i32,
+    pub hp: i32,
+    pub defense: i32,
+    pub power: i32,
+}
+
+/// How much a monster gains for every level below the first. Fractions add
+/// up over several levels and are rounded down.
+#[derive(Deserialize, Debug, Default)]
+pub struct RawStatScaling {
+    #[serde(default)]
+    pub max_hp: f32,
+    #[serde(default)]
+    pub defense: f32,
+    #[serde(default)]
+    pub power: f32,
+}
@@ -0,0 +1,84 @@
+//! Entity definitions ("raws") loaded from JSON at startup.
+//!
+//! Every `*.json` file in `resources/raws` is read and merged, so new
+//! monsters, items, traps, loot and affixes can be added without
+//! recompiling. Later files override earlier definitions of the same name.
+
+use serde::Deserialize;
+use std::{
+    fs,
+    path::Path,
+    sync::{LazyLock, Mutex},
+};
+
+mod item_structs;
+use item_structs::*;
+
+mod mob_structs;
+use mob_structs::*;
+
+mod prop_structs;
+use prop_structs::*;
+
+mod spawn_table_structs;
+use spawn_table_structs::*;
+
+mod loot_structs;
+use loot_structs::*;
+
+mod pack_structs;
+use pack_structs::*;
+
+mod affix_structs;
+use affix_structs::*;
+
+mod rawmaster;
+pub use rawmaster::*;
+
+const RAWS_PATH: &str = "./resources/raws";
+
+pub static RAWS: LazyLock<Mutex<RawMaster>> = LazyLock::new(|| Mutex::new(RawMaster::empty()));
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Raws {
+    #[serde(default)]
+    pub items: Vec<RawItem>,
+    #[serde(default)]
+    pub mobs: Vec<RawMob>,
+    #[serde(default)]
+    pub props: Vec<RawProp>,
+    #[serde(default)]
+    pub spawn_table: Vec<SpawnTableEntry>,
+    #[serde(default)]
+    pub loot_tables: Vec<RawLootTable>,
+    #[serde(default)]
+    pub packs: Vec<RawPack>,
+    #[serde(default)]
+    pub affixes: Vec<RawAffix>,
+}
+
+/// Reads every raw file and hands the result to the global `RAWS`.
+pub fn load_raws() {
+    let mut paths: Vec<_> = fs::read_dir(Path::new(RAWS_PATH))
+        .expect("Unable to read the raws directory")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut raws = Raws::default();
+    for path in paths {
+        let contents = fs::read_to_string(&path).expect("Unable to read raw file");
+        let decoded: Raws = serde_json::from_str(&contents)
+            .unwrap_or_else(|e| panic!("Unable to parse {}: {}", path.display(), e));
+        raws.items.extend(decoded.items);
+        raws.mobs.extend(decoded.mobs);
+        raws.props.extend(decoded.props);
+        raws.spawn_table.extend(decoded.spawn_table);
+        raws.loot_tables.extend(decoded.loot_tables);
+        raws.packs.extend(decoded.packs);
+        raws.affixes.extend(decoded.affixes);
+    }
+
+    RAWS.lock().unwrap().load(raws);
+}
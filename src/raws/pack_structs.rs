@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+/// Monsters that spawn as a group: the leader, with its followers gathered
+/// around it.
+#[derive(Deserialize, Debug)]
+pub struct RawPack {
+    pub name: String,
+    pub leader: String,
+    pub followers: Vec<String>,
+}
@@ -0,0 +1,21 @@
+use super::{RawEffects, RawRenderable};
+use serde::Deserialize;
+
+/// Anything on the map that is neither a monster nor an item, such as traps.
+#[derive(Deserialize, Debug)]
+pub struct RawProp {
+    pub name: String,
+    pub renderable: Option<RawRenderable>,
+    #[serde(default)]
+    pub hidden: bool,
+    pub entry_trigger: Option<RawEntryTrigger>,
+    #[serde(default)]
+    pub cost: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawEntryTrigger {
+    pub effects: RawEffects,
+    #[serde(default)]
+    pub single_activation: bool,
+}
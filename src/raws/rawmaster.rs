@@ -0,0 +1,849 @@
+use super::{RawAffix, RawEffects, RawRenderable, Raws, SpawnTableEntry};
+use crate::{
+    attributes::max_hp,
+    components::*,
+    map::{random_table::RandomTable, Map, MasterDungeonMap, TileType},
+};
+use rltk::{Point, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Where a freshly spawned entity ends up.
+pub enum SpawnType {
+    AtPosition { x: i32, y: i32 },
+    Equipped { by: Entity },
+    Carried { by: Entity },
+}
+
+/// Holds the loaded raws and indexes them by name.
+pub struct RawMaster {
+    raws: Raws,
+    item_index: HashMap<String, usize>,
+    mob_index: HashMap<String, usize>,
+    prop_index: HashMap<String, usize>,
+    loot_index: HashMap<String, usize>,
+    pack_index: HashMap<String, usize>,
+}
+
+impl RawMaster {
+    pub fn empty() -> RawMaster {
+        RawMaster {
+            raws: Raws::default(),
+            item_index: HashMap::new(),
+            mob_index: HashMap::new(),
+            prop_index: HashMap::new(),
+            loot_index: HashMap::new(),
+            pack_index: HashMap::new(),
+        }
+    }
+
+    pub fn load(&mut self, raws: Raws) {
+        self.raws = raws;
+        self.item_index = HashMap::new();
+        for (i, item) in self.raws.items.iter().enumerate() {
+            self.item_index.insert(item.name.clone(), i);
+        }
+        self.mob_index = HashMap::new();
+        for (i, mob) in self.raws.mobs.iter().enumerate() {
+            self.mob_index.insert(mob.name.clone(), i);
+        }
+        self.prop_index = HashMap::new();
+        for (i, prop) in self.raws.props.iter().enumerate() {
+            self.prop_index.insert(prop.name.clone(), i);
+        }
+        self.loot_index = HashMap::new();
+        for (i, loot) in self.raws.loot_tables.iter().enumerate() {
+            self.loot_index.insert(loot.name.clone(), i);
+        }
+        self.pack_index = HashMap::new();
+        for (i, pack) in self.raws.packs.iter().enumerate() {
+            self.pack_index.insert(pack.name.clone(), i);
+        }
+    }
+}
+
+fn get_renderable_component(renderable: &RawRenderable) -> Renderable {
+    Renderable {
+        glyph: rltk::to_cp437(renderable.glyph.chars().next().unwrap_or('?')),
+        fg: RGB::from_hex(&renderable.fg).expect("Invalid RGB"),
+        bg: RGB::from_hex(&renderable.bg).expect("Invalid RGB"),
+        render_order: renderable.order,
+    }
+}
+
+/// Adds the components for a set of effects, used by both consumables and
+/// traps.
+fn apply_effects<'a>(mut eb: EntityBuilder<'a>, effects: &RawEffects) -> EntityBuilder<'a> {
+    if let Some(heal_amount) = effects.provides_healing {
+        eb = eb.with(ProvidesHealing { heal_amount });
+    }
+    if let Some(range) = effects.ranged {
+        eb = eb.with(Ranged { range });
+    }
+    if let Some(damage) = effects.damage {
+        eb = eb.with(InflictsDamage {
+            damage,
+            damage_type: get_damage_type(effects.damage_type.as_deref().unwrap_or("physical")),
+        });
+    }
+    if let Some(radius) = effects.area_of_effect {
+        eb = eb.with(AreaOfEffect { radius });
+    }
+    if let Some(turns) = effects.confusion {
+        eb = eb.with(Confusion { turns });
+    }
+    if effects.teleports {
+        eb = eb.with(Teleports {});
+    }
+    if let Some(radius) = effects.alarm {
+        eb = eb.with(RaisesAlarm { radius });
+    }
+    if effects.identify {
+        eb = eb.with(IdentifiesItems {});
+    }
+    if effects.remove_curse {
+        eb = eb.with(RemovesCurse {});
+    }
+    eb
+}
+
+/// Spawns whatever the raws call `key`. Returns `None` if there is no such
+/// definition. Only items can be equipped or carried; anything else asked to
+/// spawn that way is refused.
+pub fn spawn_named_entity(
+    raws: &RawMaster,
+    ecs: &mut World,
+    key: &str,
+    spawn_type: SpawnType,
+) -> Option<Entity> {
+    if raws.item_index.contains_key(key) {
+        return spawn_named_item(raws, ecs, key, spawn_type);
+    }
+
+    let SpawnType::AtPosition { x, y } = spawn_type else {
+        return None;
+    };
+    if raws.mob_index.contains_key(key) {
+        return spawn_named_mob(raws, ecs, key, x, y);
+    } else if raws.pack_index.contains_key(key) {
+        return spawn_named_pack(raws, ecs, key, x, y);
+    } else if raws.prop_index.contains_key(key) {
+        return spawn_named_prop(raws, ecs, key, x, y);
+    }
+
+    None
+}
+
+fn spawn_named_item(
+    raws: &RawMaster,
+    ecs: &mut World,
+    key: &str,
+    spawn_type: SpawnType,
+) -> Option<Entity> {
+    let item_template = &raws.raws.items[raws.item_index[key]];
+    let appearance = ecs.fetch::<MasterDungeonMap>().appearance(key).cloned();
+    let depth = ecs.fetch::<Map>().depth;
+    let (blessing, affixes) = match &item_template.equippable {
+        Some(equippable) => {
+            let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+            let blessing = roll_blessing(&mut rng);
+            (
+                blessing,
+                roll_affixes(raws, &mut rng, depth, &equippable.slot),
+            )
+        }
+        None => (None, Vec::new()),
+    };
+
+    let mut eb = ecs
+        .create_entity()
+        .marked::<SimpleMarker<SerializeMe>>()
+        .with(Name {
+            name: affixed_name(&item_template.name, &affixes),
+        })
+        .with(Item {
+            weight: item_template.weight,
+        });
+
+    if let Some(renderable) = &item_template.renderable {
+        let mut renderable = get_renderable_component(renderable);
+        // Unidentified items take the colour of their disguise, so they can't give themselves away
+        if let Some(appearance) = appearance {
+            renderable.fg = RGB::from_hex(&appearance.colour).expect("Invalid RGB");
+        }
+        eb = eb.with(renderable);
+    }
+
+    if let Some(count) = item_template.ammunition {
+        eb = eb.with(Ammunition { count });
+    }
+    if let Some(damage) = &item_template.thrown_damage {
+        let (damage_n_dice, damage_die_type, damage_bonus) = parse_dice_string(damage);
+        eb = eb.with(ThrownWeapon {
+            damage_n_dice,
+            damage_die_type,
+            damage_bonus,
+        });
+    }
+    if let Some(radius) = item_template.shatters {
+        eb = eb.with(Shatters { radius });
+    }
+
+    if let Some(consumable) = &item_template.consumable {
+        eb = eb.with(Consumable {});
+        eb = apply_effects(eb, &consumable.effects);
+    }
+
+    let mut slot = None;
+    if let Some(equippable) = &item_template.equippable {
+        let equipment_slot = match equippable.slot.as_str() {
+            "Shield" => EquipmentSlot::Shield,
+            "Ranged" => EquipmentSlot::Ranged,
+            _ => EquipmentSlot::Melee,
+        };
+        slot = Some(equipment_slot);
+        eb = eb.with(Equippable {
+            slot: equipment_slot,
+        });
+        if let Some(damage) = &equippable.damage {
+            let (damage_n_dice, damage_die_type, damage_bonus) = parse_dice_string(damage);
+            eb = match equippable.range {
+                Some(range) => eb.with(RangedWeapon {
+                    range,
+                    damage_n_dice,
+                    damage_die_type,
+                    damage_bonus,
+                }),
+                None => eb.with(MeleeWeapon {
+                    damage_n_dice,
+                    damage_die_type,
+                    damage_bonus,
+                }),
+            };
+        }
+        let affix_power = affixes.iter().map(|affix| affix.power_bonus);
+        if let Some(power) = total_bonus(equippable.power_bonus, affix_power) {
+            eb = eb.with(MeleePowerBonus { power });
+        }
+        let affix_defense = affixes.iter().map(|affix| affix.defense_bonus);
+        if let Some(defense) = total_bonus(equippable.defense_bonus, affix_defense) {
+            eb = eb.with(DefensePowerBonus { defense });
+        }
+    }
+    let bonus_damage = total_bonus_damage(&affixes);
+    if !bonus_damage.is_empty() {
+        eb = eb.with(BonusDamage {
+            damage: bonus_damage,
+        });
+    }
+    if let Some(percent) = total_bonus(None, affixes.iter().map(|affix| affix.life_steal)) {
+        eb = eb.with(LifeSteal { percent });
+    }
+    match affixes.len() {
+        0 => {}
+        1 => {
+            eb = eb.with(MagicItem {
+                rarity: Rarity::Magic,
+            })
+        }
+        _ => {
+            eb = eb.with(MagicItem {
+                rarity: Rarity::Rare,
+            })
+        }
+    }
+    if let Some(blessing) = blessing {
+        eb = eb.with(Beatitude {
+            blessing,
+            known: false,
+        });
+    }
+
+    eb = match spawn_type {
+        SpawnType::AtPosition { x, y } => eb.with(Position { x, y }),
+        SpawnType::Carried { by } => eb.with(InBackpack { owner: by }),
+        SpawnType::Equipped { by } => match slot {
+            Some(slot) => eb.with(Equipped { owner: by, slot }),
+            // Can't wield it, so stow it instead
+            None => eb.with(InBackpack { owner: by }),
+        },
+    };
+
+    Some(eb.build())
+}
+
+/// Gear comes out of the dungeon cursed one time in `CURSE_CHANCE`, and
+/// blessed one time in `BLESSING_CHANCE`.
+const CURSE_CHANCE: i32 = 8;
+const BLESSING_CHANCE: i32 = 10;
+
+fn roll_blessing(rng: &mut RandomNumberGenerator) -> Option<Blessing> {
+    if rng.roll_dice(1, CURSE_CHANCE) == 1 {
+        Some(Blessing::Cursed)
+    } else if rng.roll_dice(1, BLESSING_CHANCE) == 1 {
+        Some(Blessing::Blessed)
+    } else {
+        None
+    }
+}
+
+/// Decides whether a piece of gear is plain, magic (one affix) or rare (a
+/// prefix and a suffix). The deeper it's found, the better the odds.
+fn roll_rarity(rng: &mut RandomNumberGenerator, depth: i32) -> Rarity {
+    RandomTable::new()
+        .add(Rarity::Common, 20)
+        .add(Rarity::Magic, 2 + depth * 2)
+        .add(Rarity::Rare, depth - 1)
+        .roll(rng, depth)
+        .unwrap_or(Rarity::Common)
+}
+
+/// Rolls the affixes for a piece of gear that goes in `slot`.
+fn roll_affixes<'a>(
+    raws: &'a RawMaster,
+    rng: &mut RandomNumberGenerator,
+    depth: i32,
+    slot: &str,
+) -> Vec<&'a RawAffix> {
+    let rarity = roll_rarity(rng, depth);
+    let prefix_first = rng.roll_dice(1, 2) == 1;
+    let mut pick = |kind: &str| -> Option<&'a RawAffix> {
+        let mut table = RandomTable::new();
+        for (i, affix) in raws.raws.affixes.iter().enumerate() {
+            if affix.kind == kind && affix.slot.as_ref().is_none_or(|only| only == slot) {
+                let min_depth = affix.min_depth.unwrap_or(i32::MIN);
+                table = table.add_for_depths(i, affix.weight, min_depth, i32::MAX);
+            }
+        }
+        table
+            .roll(rng, depth)
+            .map(|index| &raws.raws.affixes[index])
+    };
+
+    match rarity {
+        Rarity::Common => Vec::new(),
+        Rarity::Magic => {
+            let (first, second) = if prefix_first {
+                ("prefix", "suffix")
+            } else {
+                ("suffix", "prefix")
+            };
+            pick(first).or_else(|| pick(second)).into_iter().collect()
+        }
+        Rarity::Rare => [pick("prefix"), pick("suffix")]
+            .into_iter()
+            .flatten()
+            .collect(),
+    }
+}
+
+/// Puts prefixes in front of an item's name and suffixes after it.
+fn affixed_name(name: &str, affixes: &[&RawAffix]) -> String {
+    let mut words: Vec<&str> = affixes
+        .iter()
+        .filter(|affix| affix.kind == "prefix")
+        .map(|affix| affix.name.as_str())
+        .collect();
+    words.push(name);
+    words.extend(
+        affixes
+            .iter()
+            .filter(|affix| affix.kind == "suffix")
+            .map(|affix| affix.name.as_str()),
+    );
+    words.join(" ")
+}
+
+/// Adds a bonus from the base item to the same bonus from its affixes.
+/// `None` if nothing has that bonus at all.
+fn total_bonus(base: Option<i32>, affixes: impl Iterator<Item = Option<i32>>) -> Option<i32> {
+    affixes.fold(base, |total, bonus| match (total, bonus) {
+        (Some(total), Some(bonus)) => Some(total + bonus),
+        (total, bonus) => total.or(bonus),
+    })
+}
+
+/// Adds up the bonus damage of all the affixes, one entry per damage type.
+fn total_bonus_damage(affixes: &[&RawAffix]) -> Vec<(DamageType, i32)> {
+    let mut total: Vec<(DamageType, i32)> = Vec::new();
+    for bonus in affixes
+        .iter()
+        .filter_map(|affix| affix.bonus_damage.as_ref())
+    {
+        let damage_type = get_damage_type(&bonus.damage_type);
+        match total
+            .iter_mut()
+            .find(|(existing, _damage)| *existing == damage_type)
+        {
+            Some((_damage_type, damage)) => *damage += bonus.damage,
+            None => total.push((damage_type, bonus.damage)),
+        }
+    }
+    total
+}
+
+/// Reads a dice expression like "1d8+2", "2d6" or "1d4-1" into the number of
+/// dice, the die type and the flat bonus.
+fn parse_dice_string(dice: &str) -> (i32, i32, i32) {
+    let (n_dice, rest) = dice.split_once('d').expect("Dice need a 'd'");
+    let (die_type, bonus) = match rest.find(['+', '-']) {
+        Some(at) => (&rest[..at], rest[at..].trim_start_matches('+')),
+        None => (rest, "0"),
+    };
+    let n_dice = match n_dice.trim() {
+        "" => 1,
+        n_dice => n_dice.parse().expect("Invalid number of dice"),
+    };
+    (
+        n_dice,
+        die_type.trim().parse().expect("Invalid die type"),
+        bonus.trim().parse().expect("Invalid dice bonus"),
+    )
+}
+
+pub fn get_damage_type(name: &str) -> DamageType {
+    match name {
+        "fire" => DamageType::Fire,
+        "cold" => DamageType::Cold,
+        "poison" => DamageType::Poison,
+        "magic" => DamageType::Magic,
+        _ => DamageType::Physical,
+    }
+}
+
+/// Monsters are worth `XP_PER_COST` experience for every point of threat
+/// they cost, and a quarter more again for every level below the first.
+const XP_PER_COST: i32 = 5;
+const XP_SCALING_PER_DEPTH: f32 = 0.25;
+
+fn spawn_named_mob(raws: &RawMaster, ecs: &mut World, key: &str, x: i32, y: i32) -> Option<Entity> {
+    let mob_template = &raws.raws.mobs[raws.mob_index[key]];
+
+    // Deeper levels breed tougher monsters
+    let levels_down = (ecs.fetch::<Map>().depth - 1).max(0) as f32;
+    let scaling = &mob_template.stats_per_depth;
+    let fitness = mob_template.attributes.fitness;
+    let max_hp = max_hp(
+        mob_template.stats.max_hp + (levels_down * scaling.max_hp) as i32,
+        fitness,
+    );
+    let hp = max_hp - (mob_template.stats.max_hp - mob_template.stats.hp);
+    let base_xp = mob_template
+        .xp
+        .unwrap_or(i32::max(1, mob_template.cost) * XP_PER_COST);
+    let xp = base_xp + (levels_down * base_xp as f32 * XP_SCALING_PER_DEPTH) as i32;
+    let defense = mob_template.stats.defense + (levels_down * scaling.defense) as i32;
+    let power = mob_template.stats.power + (levels_down * scaling.power) as i32;
+
+    let mut eb = ecs
+        .create_entity()
+        .marked::<SimpleMarker<SerializeMe>>()
+        .with(Position { x, y })
+        .with(Name {
+            name: mob_template.name.clone(),
+        })
+        .with(Monster {})
+        .with(ExperienceValue { xp })
+        .with(Attributes {
+            might: mob_template.attributes.might,
+            fitness,
+            quickness: mob_template.attributes.quickness,
+            intelligence: mob_template.attributes.intelligence,
+        })
+        .with(CombatStats {
+            max_hp,
+            hp,
+            defense,
+            power,
+        })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: mob_template.vision_range,
+            dirty: true,
+        });
+
+    if let Some(renderable) = &mob_template.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    if mob_template.blocks_tile {
+        eb = eb.with(BlocksTile {});
+    }
+
+    let damage_types = |names: &[String]| -> Vec<DamageType> {
+        names.iter().map(|name| get_damage_type(name)).collect()
+    };
+    if !mob_template.resistances.is_empty() {
+        eb = eb.with(Resistances {
+            damage_types: damage_types(&mob_template.resistances),
+        });
+    }
+    if !mob_template.immunities.is_empty() {
+        eb = eb.with(Immunities {
+            damage_types: damage_types(&mob_template.immunities),
+        });
+    }
+    if !mob_template.vulnerabilities.is_empty() {
+        eb = eb.with(Vulnerabilities {
+            damage_types: damage_types(&mob_template.vulnerabilities),
+        });
+    }
+    if let Some(unique) = &mob_template.unique {
+        eb = eb.with(Unique {
+            death_message: unique.death_message.clone(),
+        });
+    }
+    if let Some(loot_table) = &mob_template.loot_table {
+        eb = eb.with(LootTable {
+            table: loot_table.clone(),
+        });
+    }
+
+    let mob = eb.build();
+    for item in mob_template.equipped.iter() {
+        spawn_named_entity(raws, ecs, item, SpawnType::Equipped { by: mob });
+    }
+    for item in mob_template.carried.iter() {
+        spawn_named_entity(raws, ecs, item, SpawnType::Carried { by: mob });
+    }
+
+    Some(mob)
+}
+
+/// Spawns a pack's leader at the given position, and its followers on the
+/// nearest free floor around it. If another pack's follower already took the
+/// spot, the leader settles for the closest free tile too. Returns the leader.
+fn spawn_named_pack(
+    raws: &RawMaster,
+    ecs: &mut World,
+    key: &str,
+    x: i32,
+    y: i32,
+) -> Option<Entity> {
+    let pack_template = &raws.raws.packs[raws.pack_index[key]];
+    if !raws.mob_index.contains_key(&pack_template.leader) {
+        return None;
+    }
+
+    let mut formation = pack_formation(ecs, Point::new(x, y), pack_template.followers.len() + 1);
+    if formation.is_empty() {
+        return None;
+    }
+    let spot = formation.remove(0);
+    let leader = spawn_named_mob(raws, ecs, &pack_template.leader, spot.x, spot.y)?;
+    for (follower, spot) in pack_template.followers.iter().zip(formation) {
+        if !raws.mob_index.contains_key(follower) {
+            continue;
+        }
+        if let Some(member) = spawn_named_mob(raws, ecs, follower, spot.x, spot.y) {
+            ecs.write_storage::<PackMember>()
+                .insert(member, PackMember { leader })
+                .expect("Unable to insert pack member");
+        }
+    }
+
+    Some(leader)
+}
+
+/// Finds up to `count` unoccupied floor tiles at or around `center`, closest
+/// first, without going through walls.
+fn pack_formation(ecs: &World, center: Point, count: usize) -> Vec<Point> {
+    const MAX_SPREAD: i32 = 4;
+
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let occupied: HashSet<usize> = positions
+        .join()
+        .map(|pos| map.xy_idx(pos.x, pos.y))
+        .collect();
+
+    let mut formation = Vec::new();
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut open_list: VecDeque<Point> = VecDeque::new();
+    let center_idx = map.xy_idx(center.x, center.y);
+    visited.insert(center_idx);
+    open_list.push_back(center);
+    if !occupied.contains(&center_idx) && count > 0 {
+        formation.push(center);
+    }
+
+    while let Some(spot) = open_list.pop_front() {
+        if formation.len() >= count {
+            break;
+        }
+        for (dx, dy) in [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ] {
+            let next = Point::new(spot.x + dx, spot.y + dy);
+            if next.x < 1 || next.x >= map.width - 1 || next.y < 1 || next.y >= map.height - 1 {
+                continue;
+            }
+            if (next.x - center.x).abs() > MAX_SPREAD || (next.y - center.y).abs() > MAX_SPREAD {
+                continue;
+            }
+            let idx = map.xy_idx(next.x, next.y);
+            if map.tiles[idx] != TileType::Floor || !visited.insert(idx) {
+                continue;
+            }
+            open_list.push_back(next);
+            if !occupied.contains(&idx) && formation.len() < count {
+                formation.push(next);
+            }
+        }
+    }
+
+    formation
+}
+
+fn spawn_named_prop(
+    raws: &RawMaster,
+    ecs: &mut World,
+    key: &str,
+    x: i32,
+    y: i32,
+) -> Option<Entity> {
+    let prop_template = &raws.raws.props[raws.prop_index[key]];
+
+    let mut eb = ecs
+        .create_entity()
+        .marked::<SimpleMarker<SerializeMe>>()
+        .with(Position { x, y })
+        .with(Name {
+            name: prop_template.name.clone(),
+        });
+
+    if let Some(renderable) = &prop_template.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    if prop_template.hidden {
+        eb = eb.with(Hidden {});
+    }
+    if let Some(entry_trigger) = &prop_template.entry_trigger {
+        eb = eb.with(EntryTrigger {});
+        eb = apply_effects(eb, &entry_trigger.effects);
+        if entry_trigger.single_activation {
+            eb = eb.with(SingleActivation {});
+        }
+    }
+
+    Some(eb.build())
+}
+
+/// Adds a raws table entry, with its weight worked out for `depth`.
+fn add_table_entry<T: Clone>(
+    rt: RandomTable<T>,
+    value: T,
+    entry: &SpawnTableEntry,
+    depth: i32,
+) -> RandomTable<T> {
+    let weight = entry.weight + (depth as f32 * entry.weight_per_depth) as i32;
+    rt.add_for_depths(
+        value,
+        weight,
+        entry.min_depth.unwrap_or(i32::MIN),
+        entry.max_depth.unwrap_or(i32::MAX),
+    )
+}
+
+/// Builds the spawn table for a depth. Entries whose weight works out to zero
+/// or less on this depth, or whose depth range excludes it, never come up.
+pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> RandomTable<String> {
+    let mut rt = RandomTable::new();
+    for entry in raws.raws.spawn_table.iter() {
+        rt = add_table_entry(rt, entry.name.clone(), entry, depth);
+    }
+    rt
+}
+
+/// What spawning `key` takes out of a level's budget. A pack costs as much
+/// as all of its members together. Nothing is free, so the budget always runs
+/// out eventually.
+pub fn get_spawn_cost(raws: &RawMaster, key: &str) -> i32 {
+    let cost = if let Some(index) = raws.item_index.get(key) {
+        raws.raws.items[*index].cost
+    } else if let Some(index) = raws.mob_index.get(key) {
+        raws.raws.mobs[*index].cost
+    } else if let Some(index) = raws.prop_index.get(key) {
+        raws.raws.props[*index].cost
+    } else if let Some(index) = raws.pack_index.get(key) {
+        let pack = &raws.raws.packs[*index];
+        std::iter::once(&pack.leader)
+            .chain(pack.followers.iter())
+            .map(|member| get_spawn_cost(raws, member))
+            .sum()
+    } else {
+        0
+    };
+    cost.max(1)
+}
+
+/// How many hit points the named item heals, zero if it isn't a healing item.
+pub fn get_healing_amount(raws: &RawMaster, key: &str) -> i32 {
+    raws.item_index
+        .get(key)
+        .and_then(|index| raws.raws.items[*index].consumable.as_ref())
+        .and_then(|consumable| consumable.effects.provides_healing)
+        .unwrap_or(0)
+}
+
+/// Lists every item type that starts out unidentified, with the kind of
+/// thing it passes itself off as.
+pub fn get_unidentified_items(raws: &RawMaster) -> Vec<(String, String)> {
+    raws.raws
+        .items
+        .iter()
+        .filter_map(|item| {
+            item.unidentified
+                .as_ref()
+                .map(|kind| (item.name.clone(), kind.clone()))
+        })
+        .collect()
+}
+
+/// Names every item that heals.
+pub fn get_healing_items(raws: &RawMaster) -> Vec<String> {
+    raws.raws
+        .items
+        .iter()
+        .filter(|item| get_healing_amount(raws, &item.name) > 0)
+        .map(|item| item.name.clone())
+        .collect()
+}
+
+/// How many followers the named pack brings along, or `None` if `key` isn't a
+/// pack.
+pub fn get_pack_size(raws: &RawMaster, key: &str) -> Option<usize> {
+    raws.pack_index
+        .get(key)
+        .map(|index| raws.raws.packs[*index].followers.len())
+}
+
+/// Names every unique monster that belongs on `depth`.
+pub fn get_uniques_for_depth(raws: &RawMaster, depth: i32) -> Vec<String> {
+    raws.raws
+        .mobs
+        .iter()
+        .filter(|mob| {
+            mob.unique
+                .as_ref()
+                .is_some_and(|unique| unique.depth == depth)
+        })
+        .map(|mob| mob.name.clone())
+        .collect()
+}
+
+/// Rolls the named loot table for something that died on `depth`, returning
+/// the names of the items it leaves behind.
+pub fn roll_loot_table(
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
+    key: &str,
+    depth: i32,
+) -> Vec<String> {
+    let Some(index) = raws.loot_index.get(key) else {
+        return Vec::new();
+    };
+    let loot = &raws.raws.loot_tables[*index];
+
+    let mut drops = RandomTable::new();
+    for drop in loot.drops.iter() {
+        drops = add_table_entry(drops, drop.name.clone(), drop, depth);
+    }
+
+    // Every roll can come up empty, so decide that first and only then pick
+    // which different items the successful rolls turned into
+    let anything = RandomTable::new()
+        .add(false, loot.nothing)
+        .add(true, drops.total_weight(depth));
+    let hits = (0..loot.rolls.unwrap_or(1))
+        .filter(|_| anything.roll(rng, depth).unwrap_or(false))
+        .count();
+    drops.roll_unique(rng, depth, hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn troll_raws(nothing: i32) -> RawMaster {
+        let json = format!(
+            r#"{{ "loot_tables": [{{
+                "name": "Troll",
+                "nothing": {},
+                "rolls": 2,
+                "drops": [
+                    {{ "name": "Health Potion", "weight": 1 }},
+                    {{ "name": "Longsword", "weight": 1 }}
+                ]
+            }}] }}"#,
+            nothing
+        );
+        let mut raws = RawMaster::empty();
+        raws.load(serde_json::from_str(&json).unwrap());
+        raws
+    }
+
+    #[test]
+    fn every_roll_can_come_up_empty() {
+        // Half the weight is "nothing", so each of the two rolls misses half
+        // the time and a quarter of the drops should be empty
+        let raws = troll_raws(2);
+        let mut rng = RandomNumberGenerator::seeded(42);
+        let mut counts = [0; 3];
+        for _ in 0..10_000 {
+            counts[roll_loot_table(&raws, &mut rng, "Troll", 1).len()] += 1;
+        }
+
+        assert!((2_300..2_700).contains(&counts[0]), "{:?}", counts);
+        assert!((4_700..5_300).contains(&counts[1]), "{:?}", counts);
+        assert!((2_300..2_700).contains(&counts[2]), "{:?}", counts);
+    }
+
+    fn affix(json: &str) -> RawAffix {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn bonus_damage_from_every_affix_counts() {
+        let flaming = affix(
+            r#"{ "name": "Flaming", "kind": "prefix", "weight": 1,
+                 "bonus_damage": { "damage_type": "fire", "damage": 2 } }"#,
+        );
+        let of_embers = affix(
+            r#"{ "name": "of Embers", "kind": "suffix", "weight": 1,
+                 "bonus_damage": { "damage_type": "fire", "damage": 1 } }"#,
+        );
+        let of_frost = affix(
+            r#"{ "name": "of Frost", "kind": "suffix", "weight": 1,
+                 "bonus_damage": { "damage_type": "cold", "damage": 3 } }"#,
+        );
+
+        assert_eq!(
+            total_bonus_damage(&[&flaming, &of_embers]),
+            vec![(DamageType::Fire, 3)]
+        );
+        assert_eq!(
+            total_bonus_damage(&[&flaming, &of_frost]),
+            vec![(DamageType::Fire, 2), (DamageType::Cold, 3)]
+        );
+        assert!(total_bonus_damage(&[]).is_empty());
+    }
+
+    #[test]
+    fn rolls_never_repeat_an_item() {
+        let raws = troll_raws(0);
+        let mut rng = RandomNumberGenerator::seeded(7);
+        for _ in 0..100 {
+            let mut drops = roll_loot_table(&raws, &mut rng, "Troll", 1);
+            drops.sort();
+            assert_eq!(drops, vec!["Health Potion", "Longsword"]);
+        }
+    }
+}
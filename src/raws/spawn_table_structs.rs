@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+/// The chance of something spawning is `weight + depth * weight_per_depth`,
+/// rounded down. It only turns up between `min_depth` and `max_depth`, if
+/// given.
+#[derive(Deserialize, Debug)]
+pub struct SpawnTableEntry {
+    pub name: String,
+    pub weight: i32,
+    #[serde(default)]
+    pub weight_per_depth: f32,
+    pub min_depth: Option<i32>,
+    pub max_depth: Option<i32>,
+}
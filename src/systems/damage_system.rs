@@ -1,16 +1,32 @@
 use crate::{
-    components::Position, map::Map, ui::gamelog::GameLog, CombatStats, Name, Player, RunState,
-    SufferDamage,
+    attributes::attr_bonus,
+    components::{
+        Attributes, DamageType, Equipped, Experience, ExperienceValue, Immunities, InBackpack,
+        KilledBy, LootTable, Position, Resistances, Unique, Vulnerabilities,
+    },
+    map::{Map, MasterDungeonMap},
+    raws::{roll_loot_table, spawn_named_entity, SpawnType, RAWS},
+    ui::gamelog::GameLog,
+    CombatStats, Name, Player, RunState, SufferDamage,
 };
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 
+use super::particle_system::ParticleBuilder;
+
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        damage_type: DamageType,
+        from: Option<Entity>,
+    ) {
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, damage_type, from));
         } else {
             let dmg = SufferDamage {
-                amount: vec![amount],
+                amount: vec![(amount, damage_type, from)],
             };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
@@ -20,19 +36,66 @@ impl SufferDamage {
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
+        WriteStorage<'a, KilledBy>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Immunities>,
+        ReadStorage<'a, Vulnerabilities>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
+        let (
+            mut stats,
+            mut damage,
+            positions,
+            mut map,
+            entities,
+            mut killed_by,
+            resistances,
+            immunities,
+            vulnerabilities,
+            names,
+            mut log,
+        ) = data;
+
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            let was_alive = stats.hp > 0;
+            for (amount, damage_type, _from) in damage.amount.iter() {
+                let has = |damage_types: Option<&Vec<DamageType>>| {
+                    damage_types.is_some_and(|damage_types| damage_types.contains(damage_type))
+                };
+                let immune = has(immunities.get(entity).map(|i| &i.damage_types));
+                let resists = has(resistances.get(entity).map(|r| &r.damage_types));
+                let vulnerable = has(vulnerabilities.get(entity).map(|v| &v.damage_types));
 
-        for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+                let name = names.get(entity).map_or("It", |name| &name.name);
+                if immune {
+                    log.entries
+                        .push(format!("{} is immune to {} damage.", name, damage_type));
+                    continue;
+                }
+                let taken = adjusted_damage(*amount, immune, resists, vulnerable);
+                if resists && !vulnerable {
+                    log.entries.push(format!(
+                        "{} resists the {} damage, taking {}.",
+                        name, damage_type, taken
+                    ));
+                } else if vulnerable && !resists {
+                    log.entries.push(format!(
+                        "{} is vulnerable to {} damage, taking {}!",
+                        name, damage_type, taken
+                    ));
+                }
+                stats.hp -= taken;
+            }
             let pos = positions.get(entity);
             if let Some(pos) = pos {
                 let idx = map.xy_idx(pos.x, pos.y);
@@ -40,12 +103,40 @@ impl<'a> System<'a> for DamageSystem {
                     map.bloodstains.insert(idx);
                 }
             }
+
+            // Whoever got the last hit in takes the credit
+            if was_alive && stats.hp <= 0 {
+                if let Some(killer) = damage
+                    .amount
+                    .iter()
+                    .rev()
+                    .find_map(|(_amount, _damage_type, from)| *from)
+                {
+                    killed_by
+                        .insert(entity, KilledBy { killer })
+                        .expect("Unable to insert killer");
+                }
+            }
         }
 
         damage.clear();
     }
 }
 
+/// How much of a hit gets through. Immunity stops all of it, resisting
+/// halves it (rounding down) and vulnerability doubles it; resisting and
+/// being vulnerable at once cancel out.
+fn adjusted_damage(amount: i32, immune: bool, resists: bool, vulnerable: bool) -> i32 {
+    if immune {
+        return 0;
+    }
+    match (resists, vulnerable) {
+        (true, false) => amount / 2,
+        (false, true) => amount * 2,
+        _ => amount,
+    }
+}
+
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
     // Using a scope to make the borrow checker happy
@@ -53,15 +144,22 @@ pub fn delete_the_dead(ecs: &mut World) {
         let combat_stats = ecs.read_storage::<CombatStats>();
         let players = ecs.read_storage::<Player>();
         let names = ecs.read_storage::<Name>();
+        let uniques = ecs.read_storage::<Unique>();
         let entities = ecs.entities();
         let mut log = ecs.write_resource::<GameLog>();
+        let mut dungeon = ecs.write_resource::<MasterDungeonMap>();
         for (entity, stats) in (&entities, &combat_stats).join() {
             if stats.hp < 1 {
                 let player = players.get(entity);
                 match player {
                     None => {
                         let victim_name = names.get(entity);
-                        if let Some(victim_name) = victim_name {
+                        if let Some(unique) = uniques.get(entity) {
+                            log.entries.push(unique.death_message.clone());
+                            if let Some(victim_name) = victim_name {
+                                dungeon.record_slain_unique(&victim_name.name);
+                            }
+                        } else if let Some(victim_name) = victim_name {
                             log.entries.push(format!("{} is dead", &victim_name.name));
                         }
                         dead.push(entity)
@@ -75,7 +173,191 @@ pub fn delete_the_dead(ecs: &mut World) {
         }
     }
 
+    drop_loot(ecs, &dead);
+    award_experience(ecs, &dead);
+
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
 }
+
+/// Experience needed to get from level `level` to the next one.
+pub fn xp_to_next_level(level: i32) -> i32 {
+    level * XP_PER_LEVEL
+}
+
+const XP_PER_LEVEL: i32 = 100;
+/// Maximum hit points gained on every level up, before fitness is counted.
+const HP_PER_LEVEL: i32 = 5;
+
+/// Hands out experience for every kill to whoever made it, and levels up
+/// anyone who has earned it. A player who levels up gets to pick a bonus.
+fn award_experience(ecs: &mut World, dead: &[Entity]) {
+    let mut player_levelled = false;
+    {
+        let player_entity = *ecs.fetch::<Entity>();
+        let killed_by = ecs.read_storage::<KilledBy>();
+        let values = ecs.read_storage::<ExperienceValue>();
+        let attributes = ecs.read_storage::<Attributes>();
+        let positions = ecs.read_storage::<Position>();
+        let mut experience = ecs.write_storage::<Experience>();
+        let mut combat_stats = ecs.write_storage::<CombatStats>();
+        let mut log = ecs.write_resource::<GameLog>();
+        let mut particle_builder = ecs.write_resource::<ParticleBuilder>();
+
+        for victim in dead.iter() {
+            let (Some(killed_by), Some(value)) = (killed_by.get(*victim), values.get(*victim))
+            else {
+                continue;
+            };
+            let killer = killed_by.killer;
+            let Some(experience) = experience.get_mut(killer) else {
+                continue;
+            };
+            experience.xp += value.xp;
+
+            while experience.xp >= xp_to_next_level(experience.level) {
+                experience.xp -= xp_to_next_level(experience.level);
+                experience.level += 1;
+
+                if let Some(stats) = combat_stats.get_mut(killer) {
+                    let fitness = attributes.get(killer).map_or(10, |attr| attr.fitness);
+                    stats.max_hp += i32::max(1, HP_PER_LEVEL + attr_bonus(fitness));
+                    stats.hp = stats.max_hp;
+                    stats.power += 1;
+                }
+
+                if killer == player_entity {
+                    experience.unspent_level_ups += 1;
+                    player_levelled = true;
+                    log.entries.push(format!(
+                        "Welcome to level {}! You feel stronger.",
+                        experience.level
+                    ));
+                    if let Some(pos) = positions.get(killer) {
+                        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0), (0, 0)] {
+                            particle_builder.request(
+                                pos.x + dx,
+                                pos.y + dy,
+                                rltk::RGB::named(rltk::GOLD),
+                                rltk::RGB::named(rltk::BLACK),
+                                rltk::to_cp437('↑'),
+                                400.0,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if player_levelled {
+        let mut runstate = ecs.write_resource::<RunState>();
+        if *runstate != RunState::GameOver {
+            *runstate = RunState::LevelUp;
+        }
+    }
+}
+
+/// Leaves everything the dead were carrying or wielding on the floor where
+/// they fell, along with whatever their loot tables come up with.
+fn drop_loot(ecs: &mut World, dead: &[Entity]) {
+    let mut to_spawn: Vec<(String, Position)> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let names = ecs.read_storage::<Name>();
+        let loot_tables = ecs.read_storage::<LootTable>();
+        let mut positions = ecs.write_storage::<Position>();
+        let mut backpack = ecs.write_storage::<InBackpack>();
+        let mut equipped = ecs.write_storage::<Equipped>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let mut log = ecs.write_resource::<GameLog>();
+        let dungeon = ecs.fetch::<MasterDungeonMap>();
+        let depth = ecs.fetch::<Map>().depth;
+
+        for victim in dead.iter() {
+            let Some(pos) = positions.get(*victim).cloned() else {
+                continue;
+            };
+            let victim_name = names.get(*victim).map_or("It", |name| &name.name);
+
+            let mut dropped: Vec<Entity> = Vec::new();
+            for (item, carried) in (&entities, &backpack).join() {
+                if carried.owner == *victim {
+                    dropped.push(item);
+                }
+            }
+            for (item, worn) in (&entities, &equipped).join() {
+                if worn.owner == *victim {
+                    dropped.push(item);
+                }
+            }
+            for item in dropped {
+                backpack.remove(item);
+                equipped.remove(item);
+                positions
+                    .insert(item, pos.clone())
+                    .expect("Unable to insert position");
+                if let Some(item_name) = names.get(item) {
+                    log.entries.push(format!(
+                        "{} drops a {}.",
+                        victim_name,
+                        dungeon.display_name(&item_name.name)
+                    ));
+                }
+            }
+
+            if let Some(loot_table) = loot_tables.get(*victim) {
+                for item in
+                    roll_loot_table(&RAWS.lock().unwrap(), &mut rng, &loot_table.table, depth)
+                {
+                    log.entries.push(format!(
+                        "{} drops a {}.",
+                        victim_name,
+                        dungeon.display_name(&item)
+                    ));
+                    to_spawn.push((item, pos.clone()));
+                }
+            }
+        }
+    }
+
+    for (item, pos) in to_spawn {
+        spawn_named_entity(
+            &RAWS.lock().unwrap(),
+            ecs,
+            &item,
+            SpawnType::AtPosition { x: pos.x, y: pos.y },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn immunity_stops_everything() {
+        assert_eq!(adjusted_damage(7, true, false, false), 0);
+        assert_eq!(adjusted_damage(7, true, true, true), 0);
+        assert_eq!(adjusted_damage(7, true, false, true), 0);
+    }
+
+    #[test]
+    fn resistance_halves_rounding_down() {
+        assert_eq!(adjusted_damage(8, false, true, false), 4);
+        assert_eq!(adjusted_damage(7, false, true, false), 3);
+        assert_eq!(adjusted_damage(1, false, true, false), 0);
+    }
+
+    #[test]
+    fn vulnerability_doubles() {
+        assert_eq!(adjusted_damage(7, false, false, true), 14);
+    }
+
+    #[test]
+    fn resistance_and_vulnerability_cancel_out() {
+        assert_eq!(adjusted_damage(7, false, true, true), 7);
+        assert_eq!(adjusted_damage(7, false, false, false), 7);
+    }
+}
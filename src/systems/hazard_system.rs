@@ -0,0 +1,76 @@
+use crate::{
+    components::{DamageType, Position},
+    map::Map,
+    ui::gamelog::GameLog,
+    CombatStats, Name, RunState, SufferDamage, TileType,
+};
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use super::particle_system::ParticleBuilder;
+
+/// Burns everything that ends its turn standing in lava.
+pub struct HazardSystem {}
+
+impl<'a> System<'a> for HazardSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            map,
+            runstate,
+            player_entity,
+            mut log,
+            mut rng,
+            mut particle_builder,
+            entities,
+            positions,
+            combat_stats,
+            names,
+            mut inflict_damage,
+        ) = data;
+
+        // Once per turn, not once for every time the systems run
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, pos, _stats) in (&entities, &positions, &combat_stats).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            if map.tiles[idx] != TileType::Lava {
+                continue;
+            }
+
+            let damage = rng.roll_dice(1, 6) + map.depth / 2;
+            SufferDamage::new_damage(&mut inflict_damage, entity, damage, DamageType::Fire, None);
+            particle_builder.request(
+                pos.x,
+                pos.y,
+                rltk::RGB::named(rltk::ORANGE),
+                rltk::RGB::named(rltk::BLACK),
+                rltk::to_cp437('^'),
+                200.0,
+            );
+
+            if entity == *player_entity {
+                log.entries.push("The lava burns you!".to_string());
+            } else if let Some(name) = names.get(entity) {
+                log.entries
+                    .push(format!("{} is burned by the lava.", &name.name));
+            }
+        }
+    }
+}
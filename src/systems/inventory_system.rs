@@ -1,13 +1,26 @@
 use crate::{
-    components::{Equippable, Equipped},
-    events::WantsToRemoveItem,
+    attributes::{attr_bonus, carry_capacity},
+    components::{
+        Ammunition, Attributes, Beatitude, Blessing, BonusDamage, DamageType, DefensePowerBonus,
+        Equippable, Equipped, IdentifiesItems, Item, LifeSteal, MeleePowerBonus, RemovesCurse,
+        Shatters, ThrownWeapon,
+    },
+    events::{WantsToRemoveItem, WantsToThrowItem},
+    map::MasterDungeonMap,
     ui::gamelog::GameLog,
     AreaOfEffect, CombatStats, Confusion, Consumable, InBackpack, InflictsDamage, Map, Name,
-    Position, ProvidesHealing, SufferDamage, WantsToDropItem, WantsToPickupItem, WantsToUseItem,
+    Position, ProvidesHealing, Renderable, SerializeMe, SufferDamage, WantsToDropItem,
+    WantsToPickupItem, WantsToUseItem,
 };
+use rltk::{Point, RandomNumberGenerator};
 use specs::prelude::*;
+use specs::saveload::{MarkerAllocator, SimpleMarker, SimpleMarkerAllocator};
 
-use super::particle_system::ParticleBuilder;
+use super::{
+    melee_combat_system::{armor_class, weapon_bonus, weapon_extras},
+    particle_system::ParticleBuilder,
+    ranged_combat_system::projectile_path,
+};
 
 pub struct ItemCollectionSystem {}
 
@@ -20,27 +33,90 @@ impl<'a> System<'a> for ItemCollectionSystem {
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, MasterDungeonMap>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Attributes>,
+        Entities<'a>,
+        WriteStorage<'a, Ammunition>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            player_entity,
+            mut gamelog,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            dungeon,
+            items,
+            equipped,
+            attributes,
+            entities,
+            mut ammunition,
+        ) = data;
 
         for pickup in wants_pickup.join() {
+            // Only so much fits on anyone's back
+            if let Some(attributes) = attributes.get(pickup.collected_by) {
+                let carried: i32 = (&items, &backpack)
+                    .join()
+                    .filter(|(_item, carried)| carried.owner == pickup.collected_by)
+                    .map(|(item, _carried)| item.weight)
+                    .chain(
+                        (&items, &equipped)
+                            .join()
+                            .filter(|(_item, worn)| worn.owner == pickup.collected_by)
+                            .map(|(item, _worn)| item.weight),
+                    )
+                    .sum();
+                let weight = items.get(pickup.item).map_or(0, |item| item.weight);
+                if carried + weight > carry_capacity(attributes.might) {
+                    if pickup.collected_by == *player_entity {
+                        gamelog.entries.push(format!(
+                            "The {} is too heavy to carry with everything else.",
+                            dungeon.display_name(&names.get(pickup.item).unwrap().name)
+                        ));
+                    }
+                    continue;
+                }
+            }
+
             positions.remove(pickup.item);
-            backpack
-                .insert(
-                    pickup.item,
-                    InBackpack {
-                        owner: pickup.collected_by,
-                    },
-                )
-                .expect("Unable to insert backpack entry");
+
+            // Arrows go into the stack already being carried, if there is one
+            let picked_up = ammunition.get(pickup.item).map(|ammo| ammo.count);
+            let item_name = names.get(pickup.item).map(|name| &name.name);
+            let stack = (&entities, &backpack, &names)
+                .join()
+                .find(|(stack, carried, name)| {
+                    *stack != pickup.item
+                        && carried.owner == pickup.collected_by
+                        && Some(&name.name) == item_name
+                })
+                .map(|(stack, _carried, _name)| stack);
+            match (picked_up, stack.and_then(|stack| ammunition.get_mut(stack))) {
+                (Some(count), Some(stack)) => {
+                    stack.count += count;
+                    entities.delete(pickup.item).expect("Delete failed");
+                }
+                _ => {
+                    backpack
+                        .insert(
+                            pickup.item,
+                            InBackpack {
+                                owner: pickup.collected_by,
+                            },
+                        )
+                        .expect("Unable to insert backpack entry");
+                }
+            }
 
             if pickup.collected_by == *player_entity {
                 gamelog.entries.push(format!(
                     "You pick up the {}.",
-                    names.get(pickup.item).unwrap().name
+                    dungeon.display_name(&names.get(pickup.item).unwrap().name)
                 ));
             }
         }
@@ -72,6 +148,11 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, InBackpack>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
+        WriteExpect<'a, MasterDungeonMap>,
+        ReadStorage<'a, IdentifiesItems>,
+        WriteStorage<'a, Beatitude>,
+        ReadStorage<'a, RemovesCurse>,
+        ReadStorage<'a, Attributes>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -94,11 +175,26 @@ impl<'a> System<'a> for ItemUseSystem {
             mut backpack,
             mut particle_builder,
             positions,
+            mut dungeon,
+            identifies,
+            mut beatitudes,
+            removes_curse,
+            attributes,
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
             let mut used_item = true;
 
+            // Using something is the surest way to find out what it is
+            if entity == *player_entity && !useitem.thrown {
+                if let Some(name) = names.get(useitem.item) {
+                    dungeon.identify(&name.name);
+                }
+            }
+            let item_name = names
+                .get(useitem.item)
+                .map_or_else(String::new, |name| dungeon.display_name(&name.name));
+
             // Targeting
             let mut targets: Vec<Entity> = Vec::new();
             match useitem.target {
@@ -112,7 +208,9 @@ impl<'a> System<'a> for ItemUseSystem {
                             // Single target in tile
                             let idx = map.xy_idx(target.x, target.y);
                             for mob in map.tile_content[idx].iter() {
-                                targets.push(*mob);
+                                if combat_stats.get(*mob).is_some() {
+                                    targets.push(*mob);
+                                }
                             }
                         }
                         Some(area_effect) => {
@@ -124,8 +222,11 @@ impl<'a> System<'a> for ItemUseSystem {
                             });
                             for tile_idx in blast_tiles.iter() {
                                 let idx = map.xy_idx(tile_idx.x, tile_idx.y);
+                                // Only creatures are caught up in it, not whatever is lying around
                                 for mob in map.tile_content[idx].iter() {
-                                    targets.push(*mob);
+                                    if combat_stats.get(*mob).is_some() {
+                                        targets.push(*mob);
+                                    }
                                 }
                                 particle_builder.request(
                                     tile_idx.x,
@@ -149,20 +250,27 @@ impl<'a> System<'a> for ItemUseSystem {
                     let target_slot = can_equip.slot;
                     let target = targets[0];
 
-                    let mut to_unequip: Vec<Entity> = Vec::new();
-                    for (item_entity, allready_equipped, name) in
-                        (&entities, &equipped, &names).join()
+                    let to_unequip: Vec<Entity> = (&entities, &equipped)
+                        .join()
+                        .filter(|(_item, worn)| worn.owner == target && worn.slot == target_slot)
+                        .map(|(item, _worn)| item)
+                        .collect();
+
+                    // Cursed gear won't make way for anything else
+                    if let Some(stuck) = to_unequip
+                        .iter()
+                        .find(|item| is_cursed(&beatitudes, **item))
                     {
-                        if allready_equipped.owner == target
-                            && allready_equipped.slot == target_slot
-                        {
-                            to_unequip.push(item_entity);
-                            if target == *player_entity {
-                                gamelog
-                                    .entries
-                                    .push(format!("You unequipped {}", name.name));
-                            }
+                        if let Some(beatitude) = beatitudes.get_mut(*stuck) {
+                            beatitude.known = true;
+                        }
+                        if target == *player_entity {
+                            gamelog.entries.push(format!(
+                                "You can't let go of the cursed {}!",
+                                names.get(*stuck).unwrap().name
+                            ));
                         }
+                        continue;
                     }
 
                     for item in to_unequip.iter() {
@@ -170,6 +278,11 @@ impl<'a> System<'a> for ItemUseSystem {
                         backpack
                             .insert(*item, InBackpack { owner: target })
                             .expect("Unable to insert into backpack");
+                        if target == *player_entity {
+                            gamelog
+                                .entries
+                                .push(format!("You unequipped {}", names.get(*item).unwrap().name));
+                        }
                     }
 
                     equipped
@@ -188,6 +301,90 @@ impl<'a> System<'a> for ItemUseSystem {
                             names.get(useitem.item).unwrap().name
                         ));
                     }
+
+                    // Putting it on is how you find out what it's like
+                    if let Some(beatitude) = beatitudes.get_mut(useitem.item) {
+                        beatitude.known = true;
+                        if target == *player_entity {
+                            gamelog.entries.push(match beatitude.blessing {
+                                Blessing::Cursed => "It's cursed! It won't come off.".to_string(),
+                                Blessing::Blessed => "It feels blessed.".to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+
+            // Identify scrolls reveal everything else the reader is carrying
+            if identifies.get(useitem.item).is_some() {
+                let mut learned_something = false;
+                for (item, carried, name) in (&entities, &backpack, &names).join() {
+                    if carried.owner != entity {
+                        continue;
+                    }
+                    if dungeon.is_unidentified(&name.name) {
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!(
+                                "The {} turns out to be a {}.",
+                                dungeon.display_name(&name.name),
+                                name.name
+                            ));
+                        }
+                        dungeon.identify(&name.name);
+                        learned_something = true;
+                    }
+                    if let Some(beatitude) = beatitudes.get_mut(item) {
+                        if !beatitude.known {
+                            beatitude.known = true;
+                            learned_something = true;
+                            if entity == *player_entity {
+                                gamelog.entries.push(format!(
+                                    "The {} is {}.",
+                                    name.name,
+                                    match beatitude.blessing {
+                                        Blessing::Cursed => "cursed",
+                                        Blessing::Blessed => "blessed",
+                                    }
+                                ));
+                            }
+                        }
+                    }
+                }
+                if !learned_something && entity == *player_entity {
+                    gamelog.entries.push("You learn nothing new.".to_string());
+                }
+            }
+
+            // Remove curse scrolls cleanse everything the reader has on them
+            if removes_curse.get(useitem.item).is_some() {
+                let owned: Vec<Entity> = (&entities, &backpack)
+                    .join()
+                    .filter(|(_item, carried)| carried.owner == entity)
+                    .map(|(item, _carried)| item)
+                    .chain(
+                        (&entities, &equipped)
+                            .join()
+                            .filter(|(_item, worn)| worn.owner == entity)
+                            .map(|(item, _worn)| item),
+                    )
+                    .collect();
+                let mut lifted = 0;
+                for item in owned {
+                    if is_cursed(&beatitudes, item) {
+                        beatitudes.remove(item);
+                        lifted += 1;
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!(
+                                "The curse on the {} lifts.",
+                                names.get(item).unwrap().name
+                            ));
+                        }
+                    }
+                }
+                if lifted == 0 && entity == *player_entity {
+                    gamelog
+                        .entries
+                        .push("You feel a fleeting warmth.".to_string());
                 }
             }
 
@@ -202,11 +399,19 @@ impl<'a> System<'a> for ItemUseSystem {
                         if let Some(stats) = stats {
                             stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
                             if entity == *player_entity {
-                                gamelog.entries.push(format!(
-                                    "You use the {}, healing {} hp.",
-                                    names.get(useitem.item).unwrap().name,
-                                    healer.heal_amount
-                                ));
+                                gamelog.entries.push(if useitem.thrown {
+                                    format!(
+                                        "The {} splashes {}, healing {} hp.",
+                                        item_name,
+                                        names.get(*target).unwrap().name,
+                                        healer.heal_amount
+                                    )
+                                } else {
+                                    format!(
+                                        "You use the {}, healing {} hp.",
+                                        item_name, healer.heal_amount
+                                    )
+                                });
                             }
                             used_item = true;
 
@@ -232,15 +437,36 @@ impl<'a> System<'a> for ItemUseSystem {
                 None => {}
                 Some(damage) => {
                     used_item = false;
+                    let damage_type = damage.damage_type;
+                    // Cleverer users get more out of their scrolls
+                    let damage = i32::max(
+                        1,
+                        damage.damage
+                            + attributes
+                                .get(entity)
+                                .map_or(0, |attributes| attr_bonus(attributes.intelligence)),
+                    );
                     for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                        SufferDamage::new_damage(
+                            &mut suffer_damage,
+                            *mob,
+                            damage,
+                            damage_type,
+                            Some(entity),
+                        );
                         if entity == *player_entity {
                             let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(useitem.item).unwrap();
-                            gamelog.entries.push(format!(
-                                "You use {} on {}, inflicting {} hp.",
-                                item_name.name, mob_name.name, damage.damage
-                            ));
+                            gamelog.entries.push(if useitem.thrown {
+                                format!(
+                                    "The {} splashes {}, inflicting {} {} damage.",
+                                    item_name, mob_name.name, damage, damage_type
+                                )
+                            } else {
+                                format!(
+                                    "You use {} on {}, inflicting {} {} damage.",
+                                    item_name, mob_name.name, damage, damage_type
+                                )
+                            });
 
                             let pos = positions.get(*mob);
                             if let Some(pos) = pos {
@@ -272,11 +498,17 @@ impl<'a> System<'a> for ItemUseSystem {
                             add_confusion.push((*mob, confusion.turns));
                             if entity == *player_entity {
                                 let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!(
-                                    "You use {} on {}, confusing them.",
-                                    item_name.name, mob_name.name
-                                ));
+                                gamelog.entries.push(if useitem.thrown {
+                                    format!(
+                                        "The {} splashes {}, confusing them.",
+                                        item_name, mob_name.name
+                                    )
+                                } else {
+                                    format!(
+                                        "You use {} on {}, confusing them.",
+                                        item_name, mob_name.name
+                                    )
+                                });
 
                                 let pos = positions.get(*mob);
                                 if let Some(pos) = pos {
@@ -300,8 +532,9 @@ impl<'a> System<'a> for ItemUseSystem {
                     .expect("Unable to insert status");
             }
 
-            // If its a consumable, we delete it on use
-            if used_item {
+            // If its a consumable, we delete it on use. A thrown potion is gone
+            // even if it splashed nobody.
+            if used_item || useitem.thrown {
                 let consumable = consumables.get(useitem.item);
                 match consumable {
                     None => {}
@@ -328,6 +561,7 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, MasterDungeonMap>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -339,6 +573,7 @@ impl<'a> System<'a> for ItemDropSystem {
             names,
             mut positions,
             mut backpack,
+            dungeon,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
@@ -362,7 +597,7 @@ impl<'a> System<'a> for ItemDropSystem {
             if entity == *player_entity {
                 gamelog.entries.push(format!(
                     "You drop the {}.",
-                    names.get(to_drop.item).unwrap().name
+                    dungeon.display_name(&names.get(to_drop.item).unwrap().name)
                 ));
             }
         }
@@ -371,20 +606,307 @@ impl<'a> System<'a> for ItemDropSystem {
     }
 }
 
+/// How far anything can be thrown.
+pub const THROW_RANGE: i32 = 6;
+
+pub struct ItemThrowSystem {}
+
+impl<'a> System<'a> for ItemThrowSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToThrowItem>,
+        WriteStorage<'a, Name>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, MasterDungeonMap>,
+        ReadStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, ThrownWeapon>,
+        ReadStorage<'a, Shatters>,
+        WriteStorage<'a, AreaOfEffect>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, DefensePowerBonus>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Beatitude>,
+        ReadStorage<'a, Attributes>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, Ammunition>,
+        (
+            WriteStorage<'a, Item>,
+            WriteStorage<'a, Renderable>,
+            WriteStorage<'a, SimpleMarker<SerializeMe>>,
+            WriteExpect<'a, SimpleMarkerAllocator<SerializeMe>>,
+        ),
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, BonusDamage>,
+        ReadStorage<'a, LifeSteal>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            map,
+            entities,
+            mut wants_throw,
+            mut names,
+            mut positions,
+            mut backpack,
+            dungeon,
+            combat_stats,
+            mut suffer_damage,
+            thrown_weapons,
+            shatters,
+            mut aoe,
+            mut wants_use,
+            defense_power_bonus,
+            equipped,
+            beatitudes,
+            attributes,
+            mut rng,
+            mut particle_builder,
+            mut ammunition,
+            (mut items, mut renderables, mut markers, mut marker_allocator),
+            melee_power_bonus,
+            bonus_damage,
+            life_steal,
+        ) = data;
+
+        for (entity, to_throw) in (&entities, &wants_throw).join() {
+            let Some(start) = positions.get(entity).map(|pos| Point::new(pos.x, pos.y)) else {
+                continue;
+            };
+            let (landing, victim) = projectile_path(&map, &combat_stats, start, to_throw.target);
+
+            // Only one arrow leaves a stack; the rest stay in the pack
+            let item = match ammunition.get_mut(to_throw.item) {
+                Some(ammo) if ammo.count > 1 => {
+                    ammo.count -= 1;
+                    let arrow = entities.create();
+                    if let Some(name) = names.get(to_throw.item).cloned() {
+                        names.insert(arrow, name).expect("Unable to insert name");
+                    }
+                    if let Some(item) = items.get(to_throw.item).cloned() {
+                        items.insert(arrow, item).expect("Unable to insert item");
+                    }
+                    if let Some(renderable) = renderables.get(to_throw.item).cloned() {
+                        renderables
+                            .insert(arrow, renderable)
+                            .expect("Unable to insert renderable");
+                    }
+                    ammunition
+                        .insert(arrow, Ammunition { count: 1 })
+                        .expect("Unable to insert ammunition");
+                    marker_allocator.mark(arrow, &mut markers);
+                    arrow
+                }
+                _ => {
+                    backpack.remove(to_throw.item);
+                    to_throw.item
+                }
+            };
+            let item_name = dungeon.display_name(&names.get(item).unwrap().name);
+            let thrower_name = &names.get(entity).unwrap().name;
+
+            // Potions break and splash whatever is in them over the landing spot
+            if let Some(shatters) = shatters.get(item) {
+                if entity == *player_entity {
+                    gamelog.entries.push(format!("The {} shatters!", item_name));
+                }
+                aoe.insert(
+                    item,
+                    AreaOfEffect {
+                        radius: shatters.radius,
+                    },
+                )
+                .expect("Unable to insert area of effect");
+                wants_use
+                    .insert(
+                        entity,
+                        WantsToUseItem {
+                            item,
+                            target: Some(landing),
+                            thrown: true,
+                        },
+                    )
+                    .expect("Unable to insert intent");
+                continue;
+            }
+
+            match (victim, thrown_weapons.get(item)) {
+                (Some(victim), Some(weapon)) => {
+                    let target_stats = combat_stats.get(victim).unwrap();
+                    let target_name = &names.get(victim).unwrap().name;
+                    let thrower_power = combat_stats.get(entity).map_or(0, |stats| stats.power);
+
+                    let armor_class = armor_class(
+                        victim,
+                        target_stats,
+                        &entities,
+                        &defense_power_bonus,
+                        &equipped,
+                        &beatitudes,
+                        &attributes,
+                    );
+                    let (aim_bonus, might_bonus) =
+                        attributes.get(entity).map_or((0, 0), |attributes| {
+                            (
+                                attr_bonus(attributes.quickness),
+                                attr_bonus(attributes.might),
+                            )
+                        });
+
+                    let item_bonus = weapon_bonus(item, &melee_power_bonus, &beatitudes);
+
+                    let natural_roll = rng.roll_dice(1, 20);
+                    let hit = natural_roll == 20
+                        || (natural_roll != 1
+                            && natural_roll + thrower_power + aim_bonus + item_bonus
+                                >= armor_class);
+                    if hit {
+                        let damage = i32::max(
+                            1,
+                            rng.roll_dice(weapon.damage_n_dice, weapon.damage_die_type)
+                                + weapon.damage_bonus
+                                + might_bonus
+                                + item_bonus,
+                        );
+                        // Once it has left the thrower's hand it has no one to drain life into
+                        let (extra_damage, _steal_percent) =
+                            weapon_extras(item, &bonus_damage, &life_steal);
+
+                        let mut message = format!(
+                            "{} throws the {} at {}, for {} physical damage",
+                            thrower_name, item_name, target_name, damage
+                        );
+                        for (damage_type, amount) in extra_damage.iter() {
+                            message += &format!(", plus {} {} damage", amount, damage_type);
+                        }
+                        gamelog.entries.push(message + ".");
+                        SufferDamage::new_damage(
+                            &mut suffer_damage,
+                            victim,
+                            damage,
+                            DamageType::Physical,
+                            Some(entity),
+                        );
+                        for (damage_type, amount) in extra_damage.iter() {
+                            SufferDamage::new_damage(
+                                &mut suffer_damage,
+                                victim,
+                                *amount,
+                                *damage_type,
+                                Some(entity),
+                            );
+                        }
+                    } else {
+                        gamelog.entries.push(format!(
+                            "{} throws the {} at {}, but misses.",
+                            thrower_name, item_name, target_name
+                        ));
+                    }
+                    particle_builder.request(
+                        landing.x,
+                        landing.y,
+                        rltk::RGB::named(if hit { rltk::ORANGE } else { rltk::CYAN }),
+                        rltk::RGB::named(rltk::BLACK),
+                        rltk::to_cp437(if hit { '‼' } else { '∙' }),
+                        200.0,
+                    );
+                }
+                (Some(victim), None) => {
+                    gamelog.entries.push(format!(
+                        "The {} bounces off {}.",
+                        item_name,
+                        names.get(victim).unwrap().name
+                    ));
+                }
+                (None, _) => {
+                    if entity == *player_entity {
+                        gamelog
+                            .entries
+                            .push(format!("You throw the {}.", item_name));
+                    }
+                }
+            }
+
+            // An arrow that lands on a stack of its kind joins it
+            let thrown_name = names.get(item).map(|name| name.name.clone());
+            let is_ammo = ammunition.get(item).is_some();
+            let lying_there = (&entities, &mut ammunition, &positions, &names)
+                .join()
+                .find(|(_stack, _ammo, pos, name)| {
+                    is_ammo
+                        && pos.x == landing.x
+                        && pos.y == landing.y
+                        && Some(&name.name) == thrown_name.as_ref()
+                });
+            if let Some((_stack, ammo, _pos, _name)) = lying_there {
+                ammo.count += 1;
+                entities.delete(item).expect("Delete failed");
+            } else {
+                positions
+                    .insert(
+                        item,
+                        Position {
+                            x: landing.x,
+                            y: landing.y,
+                        },
+                    )
+                    .expect("Unable to insert position");
+            }
+        }
+
+        wants_throw.clear();
+    }
+}
+
 pub struct ItemRemoveSystem {}
 
 impl<'a> System<'a> for ItemRemoveSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
         Entities<'a>,
         WriteStorage<'a, WantsToRemoveItem>,
+        ReadStorage<'a, Name>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Beatitude>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_remove, mut equipped, mut backpack) = data;
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_remove,
+            names,
+            mut equipped,
+            mut backpack,
+            mut beatitudes,
+        ) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
+            if is_cursed(&beatitudes, to_remove.item) {
+                if let Some(beatitude) = beatitudes.get_mut(to_remove.item) {
+                    beatitude.known = true;
+                }
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You can't take off the {}; it's cursed!",
+                        names.get(to_remove.item).unwrap().name
+                    ));
+                }
+                continue;
+            }
+
             equipped.remove(to_remove.item);
             backpack
                 .insert(to_remove.item, InBackpack { owner: entity })
@@ -394,3 +916,9 @@ impl<'a> System<'a> for ItemRemoveSystem {
         wants_remove.clear();
     }
 }
+
+fn is_cursed(beatitudes: &WriteStorage<Beatitude>, item: Entity) -> bool {
+    beatitudes
+        .get(item)
+        .is_some_and(|beatitude| beatitude.blessing == Blessing::Cursed)
+}
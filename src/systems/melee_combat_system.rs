@@ -1,12 +1,21 @@
 use crate::{
-    components::{DefensePowerBonus, Equipped, MeleePowerBonus, Position},
+    attributes::{attr_bonus, BASE_ARMOR_CLASS},
+    components::{
+        Attributes, Beatitude, Blessing, BonusDamage, DamageType, DefensePowerBonus, EquipmentSlot,
+        Equipped, LifeSteal, MeleePowerBonus, MeleeWeapon, Position,
+    },
     ui::gamelog::GameLog,
     CombatStats, Name, SufferDamage, WantsToMelee,
 };
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
+use specs::world::EntitiesRes;
 
 use super::particle_system::ParticleBuilder;
 
+/// Bare hands and claws hit for 1d4.
+const UNARMED_DAMAGE: (i32, i32, i32) = (1, 4, 0);
+
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
@@ -16,13 +25,19 @@ impl<'a> System<'a> for MeleeCombatSystem {
         WriteExpect<'a, GameLog>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
-        ReadStorage<'a, CombatStats>,
+        WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, MeleePowerBonus>,
         ReadStorage<'a, DefensePowerBonus>,
         ReadStorage<'a, Equipped>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
+        ReadStorage<'a, Beatitude>,
+        ReadStorage<'a, BonusDamage>,
+        ReadStorage<'a, LifeSteal>,
+        ReadStorage<'a, MeleeWeapon>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, Attributes>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -31,74 +46,253 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut log,
             mut wants_melee,
             names,
-            combat_stats,
+            mut combat_stats,
             mut inflict_damage,
             melee_power_bonus,
             defense_power_bonus,
             equipped,
             mut particle_builder,
             positions,
+            beatitudes,
+            bonus_damage,
+            life_steal,
+            melee_weapons,
+            mut rng,
+            attributes,
         ) = data;
 
+        let mut drained: Vec<(Entity, i32)> = Vec::new();
+
         for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             if stats.hp > 0 {
-                let mut offensive_bonus = 0;
-                for (_item_entity, power_bonus, equipped_by) in
-                    (&entities, &melee_power_bonus, &equipped).join()
-                {
-                    if equipped_by.owner == entity {
-                        offensive_bonus += power_bonus.power;
-                    }
-                }
+                let offensive_bonus = offensive_bonus(
+                    entity,
+                    &entities,
+                    &melee_power_bonus,
+                    &equipped,
+                    &beatitudes,
+                );
 
                 let target_stats = combat_stats.get(wants_melee.target).unwrap();
                 if target_stats.hp > 0 {
                     let target_name = names.get(wants_melee.target).unwrap();
 
-                    let mut defensive_bonus = 0;
-                    for (_item_entity, defense_bonus, equipped_by) in
-                        (&entities, &defense_power_bonus, &equipped).join()
-                    {
-                        if equipped_by.owner == wants_melee.target {
-                            defensive_bonus += defense_bonus.defense;
-                        }
-                    }
+                    let might_bonus = attributes
+                        .get(entity)
+                        .map_or(0, |attributes| attr_bonus(attributes.might));
+                    let armor_class = armor_class(
+                        wants_melee.target,
+                        target_stats,
+                        &entities,
+                        &defense_power_bonus,
+                        &equipped,
+                        &beatitudes,
+                        &attributes,
+                    );
 
-                    let pos = positions.get(wants_melee.target);
-                    if let Some(pos) = pos {
+                    // A natural 20 always hits and rolls the damage dice twice; a
+                    // natural 1 always misses
+                    let natural_roll = rng.roll_dice(1, 20);
+                    let attack_roll = natural_roll + stats.power + might_bonus + offensive_bonus;
+                    let critical = natural_roll == 20;
+                    let fumble = natural_roll == 1;
+                    let hit = critical || (!fumble && attack_roll >= armor_class);
+
+                    let (glyph_colour, glyph) = if critical {
+                        (rltk::RED, '☼')
+                    } else if hit {
+                        (rltk::ORANGE, '‼')
+                    } else {
+                        (rltk::CYAN, '∙')
+                    };
+                    if let Some(pos) = positions.get(wants_melee.target) {
                         particle_builder.request(
                             pos.x,
                             pos.y,
-                            rltk::RGB::named(rltk::ORANGE),
+                            rltk::RGB::named(glyph_colour),
                             rltk::RGB::named(rltk::BLACK),
-                            rltk::to_cp437('‼'),
+                            rltk::to_cp437(glyph),
                             200.0,
                         );
                     }
 
-                    let damage = i32::max(
-                        0,
-                        (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
-                    );
-
-                    if damage == 0 {
+                    if fumble {
                         log.entries.push(format!(
-                            "{} is unable to hurt {}",
+                            "{} fumbles the attack on {}.",
                             &name.name, &target_name.name
                         ));
-                    } else {
+                        continue;
+                    } else if !hit {
                         log.entries.push(format!(
-                            "{} hits {}, for {} hp.",
-                            &name.name, &target_name.name, damage
+                            "{} attacks {}, but misses.",
+                            &name.name, &target_name.name
                         ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                        continue;
+                    }
+
+                    let (n_dice, die_type, dice_bonus) = (&melee_weapons, &equipped)
+                        .join()
+                        .find(|(_weapon, equipped_by)| equipped_by.owner == entity)
+                        .map_or(UNARMED_DAMAGE, |(weapon, _equipped_by)| {
+                            (
+                                weapon.damage_n_dice,
+                                weapon.damage_die_type,
+                                weapon.damage_bonus,
+                            )
+                        });
+                    let n_dice = if critical { n_dice * 2 } else { n_dice };
+                    let damage = i32::max(
+                        1,
+                        rng.roll_dice(n_dice, die_type)
+                            + dice_bonus
+                            + might_bonus
+                            + offensive_bonus,
+                    );
+
+                    // Elemental damage from the attacker's weapon goes straight through armour
+                    let (extra_damage, steal_percent) = (&entities, &equipped)
+                        .join()
+                        .find(|(_weapon, equipped_by)| {
+                            equipped_by.owner == entity && equipped_by.slot == EquipmentSlot::Melee
+                        })
+                        .map_or((Vec::new(), 0), |(weapon, _equipped_by)| {
+                            weapon_extras(weapon, &bonus_damage, &life_steal)
+                        });
+                    let total_damage =
+                        damage + extra_damage.iter().map(|(_, amount)| amount).sum::<i32>();
+
+                    let mut message = if critical {
+                        format!(
+                            "{} lands a critical hit on {}, for {} physical damage",
+                            &name.name, &target_name.name, damage
+                        )
+                    } else {
+                        format!(
+                            "{} hits {}, for {} physical damage",
+                            &name.name, &target_name.name, damage
+                        )
+                    };
+                    for (damage_type, amount) in extra_damage.iter() {
+                        message += &format!(", plus {} {} damage", amount, damage_type);
+                    }
+                    log.entries.push(message + if critical { "!" } else { "." });
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        wants_melee.target,
+                        damage,
+                        DamageType::Physical,
+                        Some(entity),
+                    );
+                    for (damage_type, amount) in extra_damage.iter() {
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            *amount,
+                            *damage_type,
+                            Some(entity),
+                        );
+                    }
+
+                    let stolen = total_damage * steal_percent / 100;
+                    if stolen > 0 {
+                        drained.push((entity, stolen));
                     }
                 }
             }
         }
 
+        for (attacker, stolen) in drained {
+            if let Some(stats) = combat_stats.get_mut(attacker) {
+                stats.hp = i32::min(stats.max_hp, stats.hp + stolen);
+                if let Some(name) = names.get(attacker) {
+                    log.entries
+                        .push(format!("{} drains {} hp.", &name.name, stolen));
+                }
+            }
+        }
+
         wants_melee.clear();
     }
 }
+
+/// What the weapon `attacker` holds in their melee slot adds to their attack
+/// and damage rolls, blessings and curses included. A bow or a shield doesn't
+/// help in a melee.
+pub fn offensive_bonus(
+    attacker: Entity,
+    entities: &EntitiesRes,
+    melee_power_bonus: &ReadStorage<MeleePowerBonus>,
+    equipped: &ReadStorage<Equipped>,
+    beatitudes: &ReadStorage<Beatitude>,
+) -> i32 {
+    (entities, melee_power_bonus, equipped)
+        .join()
+        .filter(|(_item, _bonus, equipped_by)| {
+            equipped_by.owner == attacker && equipped_by.slot == EquipmentSlot::Melee
+        })
+        .map(|(item, _bonus, _equipped_by)| weapon_bonus(item, melee_power_bonus, beatitudes))
+        .sum()
+}
+
+/// How much `weapon` adds to hit and damage rolls, blessings and curses
+/// included, whether it is swung, shot or thrown.
+pub fn weapon_bonus(
+    weapon: Entity,
+    melee_power_bonus: &ReadStorage<MeleePowerBonus>,
+    beatitudes: &ReadStorage<Beatitude>,
+) -> i32 {
+    melee_power_bonus.get(weapon).map_or(0, |bonus| bonus.power)
+        + blessing_bonus(beatitudes.get(weapon))
+}
+
+/// The elemental damage `weapon` adds to every hit, and the percentage of the
+/// damage dealt that it drains back to whoever wields it.
+pub fn weapon_extras(
+    weapon: Entity,
+    bonus_damage: &ReadStorage<BonusDamage>,
+    life_steal: &ReadStorage<LifeSteal>,
+) -> (Vec<(DamageType, i32)>, i32) {
+    (
+        bonus_damage
+            .get(weapon)
+            .map_or_else(Vec::new, |bonus| bonus.damage.clone()),
+        life_steal.get(weapon).map_or(0, |steal| steal.percent),
+    )
+}
+
+/// What an attack roll against `victim` has to reach to hit: their own
+/// defense, the armour they wear, blessings and curses included, and how
+/// quick they are on their feet.
+pub fn armor_class(
+    victim: Entity,
+    victim_stats: &CombatStats,
+    entities: &EntitiesRes,
+    defense_power_bonus: &ReadStorage<DefensePowerBonus>,
+    equipped: &ReadStorage<Equipped>,
+    beatitudes: &ReadStorage<Beatitude>,
+    attributes: &ReadStorage<Attributes>,
+) -> i32 {
+    let mut defensive_bonus = 0;
+    for (item_entity, defense_bonus, equipped_by) in
+        (entities, defense_power_bonus, equipped).join()
+    {
+        if equipped_by.owner == victim {
+            defensive_bonus += defense_bonus.defense + blessing_bonus(beatitudes.get(item_entity));
+        }
+    }
+    let dodge_bonus = attributes
+        .get(victim)
+        .map_or(0, |attributes| attr_bonus(attributes.quickness));
+    BASE_ARMOR_CLASS + victim_stats.defense + defensive_bonus + dodge_bonus
+}
+
+/// How far a blessing or curse shifts a piece of gear's bonus.
+pub fn blessing_bonus(beatitude: Option<&Beatitude>) -> i32 {
+    match beatitude.map(|beatitude| beatitude.blessing) {
+        Some(Blessing::Blessed) => 1,
+        Some(Blessing::Cursed) => -2,
+        None => 0,
+    }
+}
@@ -1,6 +1,9 @@
 pub mod damage_system;
 pub use damage_system::*;
 
+pub mod hazard_system;
+pub use hazard_system::*;
+
 pub mod inventory_system;
 pub use inventory_system::*;
 
@@ -13,8 +16,13 @@ pub use melee_combat_system::*;
 pub mod monster_ai_system;
 pub use monster_ai_system::*;
 
+pub mod ranged_combat_system;
+pub use ranged_combat_system::*;
+
 pub mod saveload_system;
-pub use saveload_system::*;
+
+pub mod trigger_system;
+pub use trigger_system::*;
 
 pub mod visibility_system;
 pub use visibility_system::*;
@@ -1,9 +1,16 @@
-use crate::{Confusion, Map, Monster, Position, RunState, Viewshed, WantsToMelee};
-use rltk::Point;
+use crate::{
+    attributes::initiative, Alerted, Attributes, Confusion, EntityMoved, Map, Monster, PackMember,
+    Position, RunState, TileType, Viewshed, WantsToMelee,
+};
+use rltk::{Point, RandomNumberGenerator};
 use specs::prelude::*;
+use std::collections::HashMap;
 
 use super::particle_system::ParticleBuilder;
 
+/// How far an idle pack member lets its leader get before catching up.
+const FOLLOW_DISTANCE: f32 = 2.5;
+
 pub struct MonsterAI {}
 
 impl<'a> System<'a> for MonsterAI {
@@ -20,6 +27,11 @@ impl<'a> System<'a> for MonsterAI {
         WriteStorage<'a, WantsToMelee>,
         WriteStorage<'a, Confusion>,
         WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, Alerted>,
+        ReadStorage<'a, PackMember>,
+        ReadStorage<'a, Attributes>,
+        WriteExpect<'a, RandomNumberGenerator>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -35,13 +47,43 @@ impl<'a> System<'a> for MonsterAI {
             mut wants_to_melee,
             mut confused,
             mut particle_builder,
+            mut entity_moved,
+            mut alerted,
+            pack_members,
+            attributes,
+            mut rng,
         ) = data;
 
         if *runstate != RunState::MonsterTurn {
             return;
         }
 
-        for (entity, mut viewshed, _monster, mut pos) in
+        // Pack members keep an eye on their leader, and charge once it spots the player
+        let mut leader_positions: HashMap<Entity, Point> = HashMap::new();
+        let mut rallied: Vec<Entity> = Vec::new();
+        for (entity, member) in (&entities, &pack_members).join() {
+            let (Some(leader_pos), Some(leader_view)) =
+                (position.get(member.leader), viewshed.get(member.leader))
+            else {
+                continue;
+            };
+            leader_positions.insert(member.leader, Point::new(leader_pos.x, leader_pos.y));
+            if leader_view.visible_tiles.contains(&*player_pos) {
+                rallied.push(entity);
+            }
+        }
+        for entity in rallied {
+            alerted
+                .insert(entity, Alerted {})
+                .expect("Unable to insert alert");
+        }
+
+        let player_initiative = attributes
+            .get(*player_entity)
+            .map_or(0, |attributes| initiative(attributes.quickness));
+        let mut door_opened = false;
+
+        for (entity, viewshed, _monster, pos) in
             (&entities, &mut viewshed, &monster, &mut position).join()
         {
             let mut can_act = true;
@@ -64,6 +106,17 @@ impl<'a> System<'a> for MonsterAI {
                 );
             }
 
+            // Slowpokes lose one turn in ten for every point the player is quicker
+            if can_act {
+                let lag = player_initiative
+                    - attributes
+                        .get(entity)
+                        .map_or(0, |attributes| initiative(attributes.quickness));
+                if lag > 0 && rng.roll_dice(1, 10) <= lag {
+                    can_act = false;
+                }
+            }
+
             if can_act {
                 let distance =
                     rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
@@ -76,24 +129,67 @@ impl<'a> System<'a> for MonsterAI {
                             },
                         )
                         .expect("Unable to insert attack");
-                } else if viewshed.visible_tiles.contains(&*player_pos) {
-                    // Path to the player
-                    let path = rltk::a_star_search(
-                        map.xy_idx(pos.x, pos.y),
-                        map.xy_idx(player_pos.x, player_pos.y),
-                        &*map,
-                    );
-                    if path.success && path.steps.len() > 1 {
-                        let mut idx = map.xy_idx(pos.x, pos.y);
-                        map.blocked[idx] = false;
-                        pos.x = path.steps[1] as i32 % map.width;
-                        pos.y = path.steps[1] as i32 / map.width;
-                        idx = map.xy_idx(pos.x, pos.y);
-                        map.blocked[idx] = true;
-                        viewshed.dirty = true;
+                } else {
+                    let sees_player = viewshed.visible_tiles.contains(&*player_pos);
+                    // Once they see the player for themselves they don't need the alarm
+                    if sees_player {
+                        alerted.remove(entity);
+                    }
+
+                    let destination = if sees_player || alerted.get(entity).is_some() {
+                        Some(*player_pos)
+                    } else {
+                        // Idle pack members trail after their leader
+                        pack_members
+                            .get(entity)
+                            .and_then(|member| leader_positions.get(&member.leader))
+                            .filter(|leader_pos| {
+                                rltk::DistanceAlg::Pythagoras
+                                    .distance2d(Point::new(pos.x, pos.y), **leader_pos)
+                                    > FOLLOW_DISTANCE
+                            })
+                            .copied()
+                    };
+
+                    if let Some(destination) = destination {
+                        // The destination may be somebody's tile, so let the path end there
+                        let end = map.xy_idx(destination.x, destination.y);
+                        let end_blocked = map.blocked[end];
+                        map.blocked[end] = false;
+                        let path = rltk::a_star_search(map.xy_idx(pos.x, pos.y), end, &*map);
+                        map.blocked[end] = end_blocked;
+
+                        if path.success
+                            && path.steps.len() > 1
+                            && map.tiles[path.steps[1]] == TileType::ClosedDoor
+                        {
+                            // Opening the door takes the whole turn
+                            map.tiles[path.steps[1]] = TileType::OpenDoor;
+                            door_opened = true;
+                        } else if path.success
+                            && path.steps.len() > 1
+                            && !map.blocked[path.steps[1]]
+                        {
+                            let mut idx = map.xy_idx(pos.x, pos.y);
+                            map.blocked[idx] = false;
+                            pos.x = path.steps[1] as i32 % map.width;
+                            pos.y = path.steps[1] as i32 / map.width;
+                            idx = map.xy_idx(pos.x, pos.y);
+                            map.blocked[idx] = true;
+                            viewshed.dirty = true;
+                            entity_moved
+                                .insert(entity, EntityMoved {})
+                                .expect("Unable to insert marker");
+                        }
                     }
                 }
             }
         }
+
+        if door_opened {
+            for viewshed in (&mut viewshed).join() {
+                viewshed.dirty = true;
+            }
+        }
     }
 }
@@ -98,7 +98,7 @@ pub fn cull_dead_particles(ecs: &mut World, ctx: &Rltk) {
     {
         let mut particles = ecs.write_storage::<ParticleLifetime>();
         let entries = ecs.entities();
-        for (entity, mut particle) in (&entries, &mut particles).join() {
+        for (entity, particle) in (&entries, &mut particles).join() {
             particle.lifetime_ms -= ctx.frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
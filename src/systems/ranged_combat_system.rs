@@ -0,0 +1,316 @@
+use crate::{
+    attributes::attr_bonus,
+    components::{
+        Ammunition, Attributes, Beatitude, BonusDamage, DamageType, DefensePowerBonus, Equipped,
+        Item, LifeSteal, MeleePowerBonus, RangedWeapon,
+    },
+    events::WantsToShoot,
+    ui::gamelog::GameLog,
+    CombatStats, InBackpack, Map, Name, Position, Renderable, SerializeMe, SufferDamage,
+};
+use rltk::{BaseMap, LineAlg, Point, RandomNumberGenerator};
+use specs::prelude::*;
+use specs::saveload::{MarkerAllocator, SimpleMarker, SimpleMarkerAllocator};
+use specs::storage::MaskedStorage;
+use std::ops::Deref;
+
+use super::{
+    melee_combat_system::{armor_class, weapon_bonus, weapon_extras},
+    particle_system::ParticleBuilder,
+};
+
+/// One arrow in `ARROW_RECOVERY_CHANCE` survives the shot and can be picked
+/// up again where it landed.
+const ARROW_RECOVERY_CHANCE: i32 = 2;
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, WantsToShoot>,
+        WriteStorage<'a, Name>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefensePowerBonus>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Beatitude>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, RangedWeapon>,
+        WriteStorage<'a, Ammunition>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Position>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, Item>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, SimpleMarker<SerializeMe>>,
+        WriteExpect<'a, SimpleMarkerAllocator<SerializeMe>>,
+        ReadStorage<'a, BonusDamage>,
+        ReadStorage<'a, LifeSteal>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut log,
+            mut wants_shoot,
+            mut names,
+            mut combat_stats,
+            mut inflict_damage,
+            melee_power_bonus,
+            defense_power_bonus,
+            equipped,
+            beatitudes,
+            attributes,
+            ranged_weapons,
+            mut ammunition,
+            mut backpack,
+            mut positions,
+            map,
+            mut rng,
+            mut particle_builder,
+            mut items,
+            mut renderables,
+            mut markers,
+            mut marker_allocator,
+            bonus_damage,
+            life_steal,
+        ) = data;
+
+        let mut drained: Vec<(Entity, i32)> = Vec::new();
+        // Arrows that survived: (stack they came from, where they landed)
+        let mut recovered: Vec<(Entity, Point)> = Vec::new();
+
+        for (entity, wants_shoot, name, stats) in
+            (&entities, &wants_shoot, &names, &combat_stats).join()
+        {
+            if stats.hp < 1 {
+                continue;
+            }
+            let Some(start) = positions.get(entity).map(|pos| Point::new(pos.x, pos.y)) else {
+                continue;
+            };
+            let Some((bow, weapon, _equipped)) = (&entities, &ranged_weapons, &equipped)
+                .join()
+                .find(|(_bow, _weapon, equipped_by)| equipped_by.owner == entity)
+            else {
+                log.entries
+                    .push(format!("{} has nothing to shoot with.", &name.name));
+                continue;
+            };
+            let Some((quiver, ammo, _carried)) = (&entities, &mut ammunition, &backpack)
+                .join()
+                .find(|(_quiver, _ammo, carried)| carried.owner == entity)
+            else {
+                log.entries
+                    .push(format!("{} is out of arrows.", &name.name));
+                continue;
+            };
+            ammo.count -= 1;
+
+            let (landing, victim) = projectile_path(&map, &combat_stats, start, wants_shoot.target);
+
+            if let Some(victim) = victim {
+                let target_stats = combat_stats.get(victim).unwrap();
+                let target_name = &names.get(victim).unwrap().name;
+
+                let armor_class = armor_class(
+                    victim,
+                    target_stats,
+                    &entities,
+                    &defense_power_bonus,
+                    &equipped,
+                    &beatitudes,
+                    &attributes,
+                );
+
+                // Aim comes from quickness, and only the bow's own bonuses count
+                let aim_bonus = attributes
+                    .get(entity)
+                    .map_or(0, |attributes| attr_bonus(attributes.quickness));
+                let bow_bonus = weapon_bonus(bow, &melee_power_bonus, &beatitudes);
+
+                let natural_roll = rng.roll_dice(1, 20);
+                let critical = natural_roll == 20;
+                let hit = critical
+                    || (natural_roll != 1
+                        && natural_roll + stats.power + aim_bonus + bow_bonus >= armor_class);
+
+                if hit {
+                    let n_dice = if critical {
+                        weapon.damage_n_dice * 2
+                    } else {
+                        weapon.damage_n_dice
+                    };
+                    let damage = i32::max(
+                        1,
+                        rng.roll_dice(n_dice, weapon.damage_die_type)
+                            + weapon.damage_bonus
+                            + bow_bonus,
+                    );
+                    let (extra_damage, steal_percent) =
+                        weapon_extras(bow, &bonus_damage, &life_steal);
+
+                    let mut message = format!(
+                        "{} shoots {}, for {} physical damage",
+                        &name.name, target_name, damage
+                    );
+                    for (damage_type, amount) in extra_damage.iter() {
+                        message += &format!(", plus {} {} damage", amount, damage_type);
+                    }
+                    log.entries.push(message + if critical { "!" } else { "." });
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        victim,
+                        damage,
+                        DamageType::Physical,
+                        Some(entity),
+                    );
+                    for (damage_type, amount) in extra_damage.iter() {
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            victim,
+                            *amount,
+                            *damage_type,
+                            Some(entity),
+                        );
+                    }
+
+                    let total_damage =
+                        damage + extra_damage.iter().map(|(_, amount)| amount).sum::<i32>();
+                    let stolen = total_damage * steal_percent / 100;
+                    if stolen > 0 {
+                        drained.push((entity, stolen));
+                    }
+                } else {
+                    log.entries.push(format!(
+                        "{} shoots at {}, but misses.",
+                        &name.name, target_name
+                    ));
+                }
+                particle_builder.request(
+                    landing.x,
+                    landing.y,
+                    rltk::RGB::named(if hit { rltk::ORANGE } else { rltk::CYAN }),
+                    rltk::RGB::named(rltk::BLACK),
+                    rltk::to_cp437(if hit { '‼' } else { '∙' }),
+                    200.0,
+                );
+            } else {
+                log.entries
+                    .push(format!("{} shoots, and hits nothing.", &name.name));
+            }
+
+            if rng.roll_dice(1, ARROW_RECOVERY_CHANCE) == 1 {
+                recovered.push((quiver, landing));
+            }
+        }
+
+        for (shooter, stolen) in drained {
+            if let Some(stats) = combat_stats.get_mut(shooter) {
+                stats.hp = i32::min(stats.max_hp, stats.hp + stolen);
+                if let Some(name) = names.get(shooter) {
+                    log.entries
+                        .push(format!("{} drains {} hp.", &name.name, stolen));
+                }
+            }
+        }
+
+        for (quiver, landing) in recovered {
+            let quiver_name = names.get(quiver).map(|name| name.name.clone());
+            let lying_there = (&entities, &mut ammunition, &positions, &names)
+                .join()
+                .find(|(_arrows, _ammo, pos, name)| {
+                    pos.x == landing.x
+                        && pos.y == landing.y
+                        && Some(&name.name) == quiver_name.as_ref()
+                });
+            if let Some((_arrows, ammo, _pos, _name)) = lying_there {
+                ammo.count += 1;
+            } else if ammunition.get(quiver).is_some_and(|ammo| ammo.count < 1) {
+                // The last arrow in the stack is the one lying there
+                ammunition.get_mut(quiver).unwrap().count = 1;
+                backpack.remove(quiver);
+                positions
+                    .insert(
+                        quiver,
+                        Position {
+                            x: landing.x,
+                            y: landing.y,
+                        },
+                    )
+                    .expect("Unable to insert position");
+            } else {
+                let arrow = entities.create();
+                if let Some(name) = names.get(quiver).cloned() {
+                    names.insert(arrow, name).expect("Unable to insert name");
+                }
+                if let Some(item) = items.get(quiver).cloned() {
+                    items.insert(arrow, item).expect("Unable to insert item");
+                }
+                if let Some(renderable) = renderables.get(quiver).cloned() {
+                    renderables
+                        .insert(arrow, renderable)
+                        .expect("Unable to insert renderable");
+                }
+                ammunition
+                    .insert(arrow, Ammunition { count: 1 })
+                    .expect("Unable to insert ammunition");
+                positions
+                    .insert(
+                        arrow,
+                        Position {
+                            x: landing.x,
+                            y: landing.y,
+                        },
+                    )
+                    .expect("Unable to insert position");
+                marker_allocator.mark(arrow, &mut markers);
+            }
+        }
+
+        // Stacks that were shot empty and not found again are gone
+        for (quiver, ammo) in (&entities, &ammunition).join() {
+            if ammo.count < 1 {
+                entities.delete(quiver).expect("Delete failed");
+            }
+        }
+
+        wants_shoot.clear();
+    }
+}
+
+/// Follows something flying from `start` towards `target` until it hits a
+/// wall or somebody gets in its way. Returns where it comes down, and who it
+/// hit, if anybody.
+pub fn projectile_path<D: Deref<Target = MaskedStorage<CombatStats>>>(
+    map: &Map,
+    combat_stats: &Storage<CombatStats, D>,
+    start: Point,
+    target: Point,
+) -> (Point, Option<Entity>) {
+    let mut landing = start;
+    for point in rltk::line2d(LineAlg::Bresenham, start, target)
+        .into_iter()
+        .skip(1)
+    {
+        let idx = map.xy_idx(point.x, point.y);
+        if map.is_opaque(idx) {
+            break;
+        }
+        landing = point;
+        let victim = map.tile_content[idx]
+            .iter()
+            .find(|potential| combat_stats.get(**potential).is_some())
+            .copied();
+        if victim.is_some() {
+            return (landing, victim);
+        }
+    }
+    (landing, None)
+}
@@ -33,9 +33,15 @@ pub fn save_game(ecs: &mut World) {
     // Create helper
 
     let mapcopy = ecs.get_mut::<crate::map::Map>().unwrap().clone();
+    let dungeon = (*ecs.fetch::<crate::map::MasterDungeonMap>()).clone();
+    let seed = ecs.fetch::<crate::map::MasterSeed>().seed;
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper { map: mapcopy })
+        .with(SerializationHelper {
+            map: mapcopy,
+            dungeon,
+            seed,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -53,6 +59,7 @@ pub fn save_game(ecs: &mut World) {
             serializer,
             data,
             Position,
+            OtherLevelPosition,
             Renderable,
             Player,
             Viewshed,
@@ -60,8 +67,11 @@ pub fn save_game(ecs: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
+            Attributes,
+            Experience,
+            ExperienceValue,
             WantsToMelee,
+            WantsToShoot,
             Item,
             Consumable,
             Ranged,
@@ -73,11 +83,36 @@ pub fn save_game(ecs: &mut World) {
             WantsToPickupItem,
             WantsToUseItem,
             WantsToDropItem,
+            WantsToThrowItem,
             WantsToRemoveItem,
             Equippable,
             Equipped,
             MeleePowerBonus,
             DefensePowerBonus,
+            MeleeWeapon,
+            RangedWeapon,
+            ThrownWeapon,
+            Shatters,
+            Ammunition,
+            Hidden,
+            EntryTrigger,
+            SingleActivation,
+            Teleports,
+            IdentifiesItems,
+            Beatitude,
+            BonusDamage,
+            Resistances,
+            Immunities,
+            Vulnerabilities,
+            LifeSteal,
+            MagicItem,
+            RemovesCurse,
+            RaisesAlarm,
+            Alerted,
+            LootTable,
+            Unique,
+            PackMember,
+            EntityMoved,
             ParticleLifetime,
             SerializationHelper
         );
@@ -134,6 +169,7 @@ pub fn load_game(ecs: &mut World) {
             de,
             d,
             Position,
+            OtherLevelPosition,
             Renderable,
             Player,
             Viewshed,
@@ -141,8 +177,11 @@ pub fn load_game(ecs: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
+            Attributes,
+            Experience,
+            ExperienceValue,
             WantsToMelee,
+            WantsToShoot,
             Item,
             Consumable,
             Ranged,
@@ -154,11 +193,36 @@ pub fn load_game(ecs: &mut World) {
             WantsToPickupItem,
             WantsToUseItem,
             WantsToDropItem,
+            WantsToThrowItem,
             WantsToRemoveItem,
             Equippable,
             Equipped,
             MeleePowerBonus,
             DefensePowerBonus,
+            MeleeWeapon,
+            RangedWeapon,
+            ThrownWeapon,
+            Shatters,
+            Ammunition,
+            Hidden,
+            EntryTrigger,
+            SingleActivation,
+            Teleports,
+            IdentifiesItems,
+            Beatitude,
+            BonusDamage,
+            Resistances,
+            Immunities,
+            Vulnerabilities,
+            LifeSteal,
+            MagicItem,
+            RemovesCurse,
+            RaisesAlarm,
+            Alerted,
+            LootTable,
+            Unique,
+            PackMember,
+            EntityMoved,
             ParticleLifetime,
             SerializationHelper
         );
@@ -173,7 +237,11 @@ pub fn load_game(ecs: &mut World) {
         for (e, h) in (&entities, &helper).join() {
             let mut worldmap = ecs.write_resource::<crate::map::Map>();
             *worldmap = h.map.clone();
-            worldmap.tile_content = vec![Vec::new(); crate::map::MAPCOUNT];
+            worldmap.tile_content = vec![Vec::new(); (worldmap.width * worldmap.height) as usize];
+            let mut dungeon = ecs.write_resource::<crate::map::MasterDungeonMap>();
+            *dungeon = h.dungeon.clone();
+            let mut master_seed = ecs.write_resource::<crate::map::MasterSeed>();
+            *master_seed = crate::map::MasterSeed::new(h.seed);
             deleteme = Some(e);
         }
         for (e, _p, pos) in (&entities, &player, &position).join() {
@@ -0,0 +1,187 @@
+use crate::{
+    components::{
+        Alerted, AreaOfEffect, Confusion, EntryTrigger, Hidden, InflictsDamage, Monster, Name,
+        Position, RaisesAlarm, SingleActivation, Teleports, Viewshed,
+    },
+    events::EntityMoved,
+    map::{Map, TileType},
+    ui::gamelog::GameLog,
+    SufferDamage,
+};
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+use super::particle_system::ParticleBuilder;
+
+/// Sets off whatever an entity stepped on this turn.
+pub struct TriggerSystem {}
+
+impl<'a> System<'a> for TriggerSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, Point>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        Entities<'a>,
+        WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Viewshed>,
+        ReadStorage<'a, EntryTrigger>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Monster>,
+        ReadStorage<'a, InflictsDamage>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, AreaOfEffect>,
+        ReadStorage<'a, Teleports>,
+        ReadStorage<'a, RaisesAlarm>,
+        WriteStorage<'a, Alerted>,
+        ReadStorage<'a, SingleActivation>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            map,
+            player_entity,
+            mut player_pos,
+            mut log,
+            mut rng,
+            mut particle_builder,
+            entities,
+            mut entity_moved,
+            mut positions,
+            mut viewsheds,
+            entry_triggers,
+            mut hidden,
+            names,
+            monsters,
+            inflicts_damage,
+            mut inflict_damage,
+            mut confusion,
+            aoe,
+            teleports,
+            alarms,
+            mut alerted,
+            single_activation,
+        ) = data;
+
+        let moved: Vec<(Entity, i32, i32)> = (&entities, &entity_moved, &positions)
+            .join()
+            .map(|(entity, _moved, pos)| (entity, pos.x, pos.y))
+            .collect();
+        entity_moved.clear();
+
+        let mut used_up: Vec<Entity> = Vec::new();
+        for (victim, x, y) in moved {
+            let idx = map.xy_idx(x, y);
+            for trap in map.tile_content[idx].iter() {
+                if *trap == victim || entry_triggers.get(*trap).is_none() {
+                    continue;
+                }
+
+                hidden.remove(*trap);
+                let trap_name = names.get(*trap).map_or("trap", |name| &name.name);
+                if victim == *player_entity {
+                    log.entries.push(format!("You trigger a {}!", trap_name));
+                } else if let Some(name) = names.get(victim) {
+                    log.entries
+                        .push(format!("{} triggers a {}!", &name.name, trap_name));
+                }
+
+                if let Some(damage) = inflicts_damage.get(*trap) {
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        victim,
+                        damage.damage,
+                        damage.damage_type,
+                        None,
+                    );
+                    particle_builder.request(
+                        x,
+                        y,
+                        rltk::RGB::named(rltk::ORANGE),
+                        rltk::RGB::named(rltk::BLACK),
+                        rltk::to_cp437('‼'),
+                        200.0,
+                    );
+                }
+
+                if let Some(confuses) = confusion.get(*trap).cloned() {
+                    let radius = aoe.get(*trap).map_or(0, |aoe| aoe.radius);
+                    let mut gassed = vec![victim];
+                    if radius > 0 {
+                        for tile in rltk::field_of_view(Point::new(x, y), radius, &*map).iter() {
+                            let tile_idx = map.xy_idx(tile.x, tile.y);
+                            gassed.extend(map.tile_content[tile_idx].iter().cloned());
+                            particle_builder.request(
+                                tile.x,
+                                tile.y,
+                                rltk::RGB::named(rltk::MAGENTA),
+                                rltk::RGB::named(rltk::BLACK),
+                                rltk::to_cp437('░'),
+                                200.0,
+                            );
+                        }
+                    }
+
+                    for target in gassed {
+                        if target == *player_entity || monsters.get(target).is_some() {
+                            confusion
+                                .insert(target, confuses.clone())
+                                .expect("Unable to insert status");
+                        }
+                    }
+                }
+
+                if teleports.get(*trap).is_some() {
+                    let floor: Vec<usize> = map
+                        .tiles
+                        .iter()
+                        .enumerate()
+                        .filter(|(_idx, tile)| **tile == TileType::Floor)
+                        .map(|(idx, _tile)| idx)
+                        .collect();
+                    if let Some(destination) = rng.random_slice_entry(&floor) {
+                        let dest_x = *destination as i32 % map.width;
+                        let dest_y = *destination as i32 / map.width;
+                        if let Some(pos) = positions.get_mut(victim) {
+                            pos.x = dest_x;
+                            pos.y = dest_y;
+                        }
+                        if victim == *player_entity {
+                            *player_pos = Point::new(dest_x, dest_y);
+                        }
+                        if let Some(viewshed) = viewsheds.get_mut(victim) {
+                            viewshed.dirty = true;
+                        }
+                    }
+                }
+
+                if let Some(alarm) = alarms.get(*trap) {
+                    log.entries.push("A loud alarm rings out!".to_string());
+                    for (monster, _monster, pos) in (&entities, &monsters, &positions).join() {
+                        let distance = rltk::DistanceAlg::Pythagoras
+                            .distance2d(Point::new(x, y), Point::new(pos.x, pos.y));
+                        if distance <= alarm.radius as f32 {
+                            alerted
+                                .insert(monster, Alerted {})
+                                .expect("Unable to insert alert");
+                        }
+                    }
+                }
+
+                if single_activation.get(*trap).is_some() {
+                    used_up.push(*trap);
+                }
+            }
+        }
+
+        for trap in used_up {
+            entities.delete(trap).expect("Unable to delete trap");
+        }
+    }
+}
@@ -1,20 +1,26 @@
-use crate::{Map, Player, Position, Viewshed};
-use rltk::{field_of_view, Point};
+use crate::{ui::gamelog::GameLog, Hidden, Map, Name, Player, Position, Viewshed};
+use rltk::{field_of_view, Point, RandomNumberGenerator};
 use specs::prelude::*;
 
 pub struct VisibilitySystem {}
 
 impl<'a> System<'a> for VisibilitySystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, Player>,
+        WriteStorage<'a, Hidden>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Name>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player) = data;
+        let (mut map, entities, mut viewshed, pos, player, mut hidden, mut rng, mut log, names) =
+            data;
 
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
@@ -35,6 +41,16 @@ impl<'a> System<'a> for VisibilitySystem {
                         let idx = map.xy_idx(vis.x, vis.y);
                         map.revealed_tiles[idx] = true;
                         map.visible_tiles[idx] = true;
+
+                        // Passive perception: every fresh look has a small chance to spot traps
+                        for e in map.tile_content[idx].iter() {
+                            if hidden.get(*e).is_some() && rng.roll_dice(1, 24) == 1 {
+                                if let Some(name) = names.get(*e) {
+                                    log.entries.push(format!("You spotted a {}.", &name.name));
+                                }
+                                hidden.remove(*e);
+                            }
+                        }
                     }
                 }
             }
@@ -0,0 +1,109 @@
+use crate::{
+    components::{Hidden, Position, Renderable},
+    map::{get_tile_glyph, Map},
+};
+use rltk::{Point, Rltk, RGB};
+use specs::prelude::*;
+
+/// Size of the part of the screen the map is drawn into; the rows below are
+/// taken by the log and status bar.
+pub const VIEW_WIDTH: i32 = 80;
+pub const VIEW_HEIGHT: i32 = 43;
+
+/// Returns the map area currently on screen as `(min_x, max_x, min_y, max_y)`,
+/// with the max values exclusive. The view is centered on the player but
+/// stops scrolling at the map edges.
+pub fn get_screen_bounds(ecs: &World) -> (i32, i32, i32, i32) {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+
+    let min_x = scroll_offset(player_pos.x, VIEW_WIDTH, map.width);
+    let min_y = scroll_offset(player_pos.y, VIEW_HEIGHT, map.height);
+
+    (min_x, min_x + VIEW_WIDTH, min_y, min_y + VIEW_HEIGHT)
+}
+
+fn scroll_offset(center: i32, view_size: i32, map_size: i32) -> i32 {
+    if map_size <= view_size {
+        // Small maps sit in the middle of the view
+        return (map_size - view_size) / 2;
+    }
+    (center - view_size / 2).clamp(0, map_size - view_size)
+}
+
+/// Translates a map position into screen coordinates.
+pub fn map_to_screen(ecs: &World, map_pos: Point) -> Point {
+    let (min_x, _, min_y, _) = get_screen_bounds(ecs);
+    Point::new(map_pos.x - min_x, map_pos.y - min_y)
+}
+
+/// Translates a screen position (e.g. the mouse) into map coordinates. Returns
+/// `None` if it isn't over the map view.
+pub fn screen_to_map(ecs: &World, screen_pos: (i32, i32)) -> Option<Point> {
+    if screen_pos.0 < 0
+        || screen_pos.0 >= VIEW_WIDTH
+        || screen_pos.1 < 0
+        || screen_pos.1 >= VIEW_HEIGHT
+    {
+        return None;
+    }
+    let (min_x, _, min_y, _) = get_screen_bounds(ecs);
+    let map = ecs.fetch::<Map>();
+    let map_pos = Point::new(screen_pos.0 + min_x, screen_pos.1 + min_y);
+    if map_pos.x < 0 || map_pos.x >= map.width || map_pos.y < 0 || map_pos.y >= map.height {
+        return None;
+    }
+    Some(map_pos)
+}
+
+/// Draws the visible part of the map and every entity on it.
+pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs);
+
+    for (screen_y, ty) in (min_y..max_y).enumerate() {
+        for (screen_x, tx) in (min_x..max_x).enumerate() {
+            if tx < 0 || tx >= map.width || ty < 0 || ty >= map.height {
+                continue;
+            }
+            let idx = map.xy_idx(tx, ty);
+            if map.revealed_tiles[idx] {
+                let (glyph, fg, bg) = get_tile_glyph(idx, &map);
+                ctx.set(screen_x as i32, screen_y as i32, fg, bg, glyph);
+            }
+        }
+    }
+
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mut data = (&positions, &renderables, !&hidden)
+        .join()
+        .map(|(pos, render, _hidden)| (pos, render))
+        .collect::<Vec<_>>();
+    data.sort_by_key(|&(_, render)| std::cmp::Reverse(render.render_order));
+    for (pos, render) in data.iter() {
+        if pos.x < min_x || pos.x >= max_x || pos.y < min_y || pos.y >= max_y {
+            continue;
+        }
+        let idx = map.xy_idx(pos.x, pos.y);
+        if map.visible_tiles[idx] {
+            ctx.set(
+                pos.x - min_x,
+                pos.y - min_y,
+                render.fg,
+                render.bg,
+                render.glyph,
+            );
+        }
+    }
+}
+
+/// Highlights a map tile, if it is on screen.
+pub fn set_map_bg(ecs: &World, ctx: &mut Rltk, map_pos: Point, bg: RGB) {
+    let screen = map_to_screen(ecs, map_pos);
+    if screen.x >= 0 && screen.x < VIEW_WIDTH && screen.y >= 0 && screen.y < VIEW_HEIGHT {
+        ctx.set_bg(screen.x, screen.y, bg);
+    }
+}
@@ -1,8 +1,19 @@
-use crate::{components::*, map::Map, systems::saveload_system, RunState, State};
+use crate::{
+    attributes::{attr_bonus, carry_capacity, initiative},
+    components::*,
+    map::{Map, MasterDungeonMap, MasterSeed},
+    player::LEVEL_UP_ATTRIBUTE_BONUS,
+    systems::{
+        damage_system::xp_to_next_level,
+        melee_combat_system::{armor_class, offensive_bonus},
+        saveload_system,
+    },
+    RunState, State,
+};
 use rltk::{Point, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
 
-use super::gamelog::GameLog;
+use super::{camera, gamelog::GameLog};
 
 pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     ctx.draw_box(
@@ -48,12 +59,8 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     );
 
     let log = ecs.fetch::<GameLog>();
-    let mut y = 44;
-    for s in log.entries.iter().rev() {
-        if y < 49 {
-            ctx.print(2, y, s);
-        }
-        y += 1;
+    for (y, s) in (44..49).zip(log.entries.iter().rev()) {
+        ctx.print(2, y, s);
     }
 
     // Draw mouse cursor
@@ -65,17 +72,29 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
 fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
+    let dungeon = ecs.fetch::<MasterDungeonMap>();
     let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let beatitudes = ecs.read_storage::<Beatitude>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let entities = ecs.entities();
 
     let mouse_pos = ctx.mouse_pos();
-    if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
-        return;
-    }
+    let mouse_map_pos = match camera::screen_to_map(ecs, mouse_pos) {
+        Some(pos) => pos,
+        None => return,
+    };
     let mut tooltip: Vec<String> = Vec::new();
-    for (name, position) in (&names, &positions).join() {
+    for (entity, name, position, _hidden) in (&entities, &names, &positions, !&hidden).join() {
         let idx = map.xy_idx(position.x, position.y);
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
-            tooltip.push(name.name.to_string());
+        if position.x == mouse_map_pos.x && position.y == mouse_map_pos.y && map.visible_tiles[idx]
+        {
+            tooltip.push(item_label(
+                &dungeon,
+                name,
+                beatitudes.get(entity),
+                ammunition.get(entity),
+            ));
         }
     }
 
@@ -91,8 +110,7 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         if mouse_pos.0 > 40 {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x,
                     y,
@@ -107,23 +125,21 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::GREY),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::GREY),
-                &"->".to_string(),
+                "->",
             );
         } else {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x + 1,
                     y,
@@ -138,22 +154,55 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::GREY),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::GREY),
-                &"<-".to_string(),
+                "<-",
             );
         }
     }
 }
 
+/// An item's name as the player knows it, owning up to a blessing or curse
+/// once that has been found out. Stacks of arrows say how many are left.
+fn item_label(
+    dungeon: &MasterDungeonMap,
+    name: &Name,
+    beatitude: Option<&Beatitude>,
+    ammunition: Option<&Ammunition>,
+) -> String {
+    let mut name = dungeon.display_name(&name.name);
+    if let Some(ammunition) = ammunition {
+        name = format!("{} ({})", name, ammunition.count);
+    }
+    match beatitude {
+        Some(beatitude) if beatitude.known => match beatitude.blessing {
+            Blessing::Blessed => format!("blessed {}", name),
+            Blessing::Cursed => format!("cursed {}", name),
+        },
+        _ => name,
+    }
+}
+
+/// Known curses show up red; otherwise magic gear is blue and rare gear gold.
+fn item_colour(magic_item: Option<&MagicItem>, beatitude: Option<&Beatitude>) -> RGB {
+    if beatitude.is_some_and(|beatitude| beatitude.known && beatitude.blessing == Blessing::Cursed)
+    {
+        return RGB::named(rltk::RED);
+    }
+    match magic_item.map(|magic_item| magic_item.rarity) {
+        Some(Rarity::Rare) => RGB::named(rltk::GOLD),
+        Some(Rarity::Magic) => RGB::named(rltk::CYAN),
+        _ => RGB::named(rltk::WHITE),
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum ItemMenuResult {
     Cancel,
@@ -161,18 +210,28 @@ pub enum ItemMenuResult {
     Selected,
 }
 
-pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
+/// Lists the items `filter` picks out under `title`, one letter each, and
+/// returns the one whose letter gets pressed.
+fn item_menu(
+    ecs: &World,
+    ctx: &mut Rltk,
+    title: &str,
+    filter: impl Fn(Entity) -> bool,
+) -> (ItemMenuResult, Option<Entity>) {
+    let names = ecs.read_storage::<Name>();
+    let dungeon = ecs.fetch::<MasterDungeonMap>();
+    let beatitudes = ecs.read_storage::<Beatitude>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let magic_items = ecs.read_storage::<MagicItem>();
+    let entities = ecs.entities();
 
-    let inventory = (&backpack, &names)
+    let items: Vec<(Entity, &Name)> = (&entities, &names)
         .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+        .filter(|(entity, _name)| filter(*entity))
+        .collect();
+    let count = items.len();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -186,7 +245,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Inventory",
+        title,
     );
     ctx.print_color(
         18,
@@ -196,12 +255,8 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         "ESCAPE to cancel",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-    {
+    for (j, (entity, name)) in items.iter().enumerate() {
+        let y = y + j as i32;
         ctx.set(
             17,
             y,
@@ -224,10 +279,18 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-        j += 1;
+        ctx.print_color(
+            21,
+            y,
+            item_colour(magic_items.get(*entity), beatitudes.get(*entity)),
+            RGB::named(rltk::BLACK),
+            item_label(
+                &dungeon,
+                name,
+                beatitudes.get(*entity),
+                ammunition.get(*entity),
+            ),
+        );
     }
 
     match ctx.key {
@@ -237,10 +300,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             _ => {
                 let selection = rltk::letter_to_option(key);
                 if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
+                    return (ItemMenuResult::Selected, Some(items[selection as usize].0));
                 }
                 (ItemMenuResult::NoResponse, None)
             }
@@ -248,23 +308,131 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     }
 }
 
-pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
+/// Picks out everything the player is carrying in their backpack.
+fn carried_items(gs: &State, ctx: &mut Rltk, title: &str) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = *gs.ecs.fetch::<Entity>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
+    item_menu(&gs.ecs, ctx, title, |item| {
+        backpack
+            .get(item)
+            .is_some_and(|carried| carried.owner == player_entity)
+    })
+}
+
+pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    carried_items(gs, ctx, "Inventory")
+}
+
+pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    carried_items(gs, ctx, "Drop Which Item?")
+}
 
-    let inventory = (&backpack, &names)
+pub fn throw_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    carried_items(gs, ctx, "Throw Which Item?")
+}
+
+pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    item_menu(&gs.ecs, ctx, "Remove Which Item?", |item| {
+        equipped
+            .get(item)
+            .is_some_and(|worn| worn.owner == player_entity)
+    })
+}
+
+/// Shows the player's attributes and what they add up to. Any key closes it.
+pub fn show_character_sheet(ecs: &World, ctx: &mut Rltk) -> ItemMenuResult {
+    let player_entity = ecs.fetch::<Entity>();
+    let all_attributes = ecs.read_storage::<Attributes>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let experience = ecs.read_storage::<Experience>();
+    let items = ecs.read_storage::<Item>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let beatitudes = ecs.read_storage::<Beatitude>();
+    let melee_power_bonus = ecs.read_storage::<MeleePowerBonus>();
+    let defense_power_bonus = ecs.read_storage::<DefensePowerBonus>();
+    let entities = ecs.entities();
+
+    let (Some(attributes), Some(stats)) = (
+        all_attributes.get(*player_entity),
+        combat_stats.get(*player_entity),
+    ) else {
+        return ItemMenuResult::Cancel;
+    };
+    let carried: i32 = (&items, &backpack)
         .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+        .filter(|(_item, carried)| carried.owner == *player_entity)
+        .map(|(item, _carried)| item.weight)
+        .chain(
+            (&items, &equipped)
+                .join()
+                .filter(|(_item, worn)| worn.owner == *player_entity)
+                .map(|(item, _worn)| item.weight),
+        )
+        .sum();
+
+    // Worked out the same way the combat systems do it
+    let gear_bonus = offensive_bonus(
+        *player_entity,
+        &entities,
+        &melee_power_bonus,
+        &equipped,
+        &beatitudes,
+    );
 
-    let mut y = (25 - (count / 2)) as i32;
+    let signed = |value: i32| format!("{:+}", value);
+    let lines = [
+        ("Might", attributes.might),
+        ("Fitness", attributes.fitness),
+        ("Quickness", attributes.quickness),
+        ("Intelligence", attributes.intelligence),
+    ];
+    let (level, xp) = experience
+        .get(*player_entity)
+        .map_or((1, 0), |exp| (exp.level, exp.xp));
+    let derived = [
+        (
+            "Level",
+            format!("{} ({} / {} xp)", level, xp, xp_to_next_level(level)),
+        ),
+        ("Hit points", format!("{} / {}", stats.hp, stats.max_hp)),
+        (
+            "Attack",
+            signed(stats.power + attr_bonus(attributes.might) + gear_bonus),
+        ),
+        ("Damage", signed(attr_bonus(attributes.might) + gear_bonus)),
+        (
+            "Armour class",
+            format!(
+                "{}",
+                armor_class(
+                    *player_entity,
+                    stats,
+                    &entities,
+                    &defense_power_bonus,
+                    &equipped,
+                    &beatitudes,
+                    &all_attributes,
+                )
+            ),
+        ),
+        ("Initiative", signed(initiative(attributes.quickness))),
+        ("Scroll power", signed(attr_bonus(attributes.intelligence))),
+        (
+            "Carrying",
+            format!("{} / {}", carried, carry_capacity(attributes.might)),
+        ),
+    ];
+
+    let height = (lines.len() + derived.len() + 5) as i32;
+    let y = 25 - height / 2;
     ctx.draw_box(
         15,
         y - 2,
-        31,
-        (count + 3) as i32,
+        40,
+        height,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
     );
@@ -273,86 +441,80 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Drop Which Item?",
+        "Character",
     );
     ctx.print_color(
         18,
-        y + count as i32 + 1,
+        y + height - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
+        "Press any key to close",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-    {
-        ctx.set(
-            17,
-            y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437('('),
-        );
-        ctx.set(
-            18,
+    for (j, (name, value)) in lines.iter().enumerate() {
+        let y = y + j as i32;
+        ctx.print(17, y, name);
+        ctx.print(
+            32,
             y,
-            RGB::named(rltk::YELLOW),
-            RGB::named(rltk::BLACK),
-            97 + j as rltk::FontCharType,
+            format!("{:>3}  ({})", value, signed(attr_bonus(*value))),
         );
-        ctx.set(
-            19,
-            y,
-            RGB::named(rltk::WHITE),
+    }
+    let y = y + lines.len() as i32 + 1;
+    for (j, (name, value)) in derived.iter().enumerate() {
+        ctx.print_color(
+            17,
+            y + j as i32,
+            RGB::named(rltk::GREY),
             RGB::named(rltk::BLACK),
-            rltk::to_cp437(')'),
+            name,
         );
-
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-        j += 1;
+        ctx.print(32, y + j as i32, value);
     }
 
     match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
+        None => ItemMenuResult::NoResponse,
+        Some(_) => ItemMenuResult::Cancel,
     }
 }
 
-pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<Equipped>();
-    let entities = gs.ecs.entities();
-
-    let inventory = (&backpack, &names)
-        .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+#[derive(PartialEq, Copy, Clone)]
+pub enum LevelUpBonus {
+    Might,
+    Fitness,
+    Quickness,
+    Intelligence,
+}
 
-    let mut y = (25 - (count / 2)) as i32;
+/// Asks the player which attribute to raise after a level up. There's no
+/// getting out of it.
+pub fn level_up_menu(ecs: &World, ctx: &mut Rltk) -> (ItemMenuResult, Option<LevelUpBonus>) {
+    let player_entity = ecs.fetch::<Entity>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let experience = ecs.read_storage::<Experience>();
+    let Some(attributes) = attributes.get(*player_entity) else {
+        return (ItemMenuResult::Cancel, None);
+    };
+    let level = experience.get(*player_entity).map_or(1, |exp| exp.level);
+
+    let choices = [
+        (LevelUpBonus::Might, "Might", attributes.might),
+        (LevelUpBonus::Fitness, "Fitness", attributes.fitness),
+        (LevelUpBonus::Quickness, "Quickness", attributes.quickness),
+        (
+            LevelUpBonus::Intelligence,
+            "Intelligence",
+            attributes.intelligence,
+        ),
+    ];
+
+    let y = 25 - (choices.len() / 2) as i32;
     ctx.draw_box(
         15,
         y - 2,
-        31,
-        (count + 3) as i32,
-        RGB::named(rltk::WHITE),
+        40,
+        (choices.len() + 3) as i32,
+        RGB::named(rltk::GOLD),
         RGB::named(rltk::BLACK),
     );
     ctx.print_color(
@@ -360,22 +522,11 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
         y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Remove Which Item?",
-    );
-    ctx.print_color(
-        18,
-        y + count as i32 + 1,
-        RGB::named(rltk::YELLOW),
-        RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
+        format!("Level {}! Pick a bonus", level),
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-    {
+    for (j, (_bonus, name, value)) in choices.iter().enumerate() {
+        let y = y + j as i32;
         ctx.set(
             17,
             y,
@@ -397,28 +548,25 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
             RGB::named(rltk::BLACK),
             rltk::to_cp437(')'),
         );
-
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-        j += 1;
+        ctx.print(
+            21,
+            y,
+            format!("+{} {} (now {})", LEVEL_UP_ATTRIBUTE_BONUS, name, value),
+        );
     }
 
     match ctx.key {
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < choices.len() as i32 {
+                return (
+                    ItemMenuResult::Selected,
+                    Some(choices[selection as usize].0),
+                );
             }
-        },
+            (ItemMenuResult::NoResponse, None)
+        }
     }
 }
 
@@ -447,7 +595,7 @@ pub fn ranged_target(
         for idx in visible.visible_tiles.iter() {
             let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
             if distance <= range as f32 {
-                ctx.set_bg(idx.x, idx.y, RGB::named(rltk::BLUE));
+                camera::set_map_bg(&gs.ecs, ctx, *idx, RGB::named(rltk::BLUE));
                 available_cells.push(idx);
             }
         }
@@ -457,19 +605,19 @@ pub fn ranged_target(
 
     // Draw mouse cursor
     let mouse_pos = ctx.mouse_pos();
+    let mouse_map_pos = camera::screen_to_map(&gs.ecs, mouse_pos);
     let mut valid_target = false;
-    for idx in available_cells.iter() {
-        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 {
-            valid_target = true;
+    if let Some(target) = mouse_map_pos {
+        for idx in available_cells.iter() {
+            if idx.x == target.x && idx.y == target.y {
+                valid_target = true;
+            }
         }
     }
     if valid_target {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
         if ctx.left_click {
-            return (
-                ItemMenuResult::Selected,
-                Some(Point::new(mouse_pos.0, mouse_pos.1)),
-            );
+            return (ItemMenuResult::Selected, mouse_map_pos);
         }
     } else {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::RED));
@@ -485,6 +633,11 @@ pub fn ranged_target(
 pub enum MainMenuSelection {
     NewGame,
     LoadGame,
+    /// `typed` is set once the player starts typing a seed of their own, so
+    /// the first digit replaces the rolled seed instead of adding to it.
+    Seed {
+        typed: bool,
+    },
     Quit,
 }
 
@@ -543,15 +696,38 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
             }
         }
 
-        if selection == MainMenuSelection::Quit {
+        let seed = gs.ecs.fetch::<MasterSeed>().seed;
+        if matches!(selection, MainMenuSelection::Seed { .. }) {
+            ctx.print_color_centered(
+                26,
+                RGB::named(rltk::MAGENTA),
+                RGB::named(rltk::BLACK),
+                format!("Seed: {}", seed),
+            );
+            ctx.print_color_centered(
+                30,
+                RGB::named(rltk::GREY),
+                RGB::named(rltk::BLACK),
+                "Type a number, or R for a random seed, then Enter",
+            );
+        } else {
             ctx.print_color_centered(
                 26,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                format!("Seed: {}", seed),
+            );
+        }
+
+        if selection == MainMenuSelection::Quit {
+            ctx.print_color_centered(
+                27,
                 RGB::named(rltk::MAGENTA),
                 RGB::named(rltk::BLACK),
                 "Quit",
             );
         } else {
-            ctx.print_color_centered(26, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
+            ctx.print_color_centered(27, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
         }
 
         match ctx.key {
@@ -571,7 +747,12 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                     match selection {
                         MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
                         MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame,
+                        MainMenuSelection::Seed { .. } => {
+                            newselection = MainMenuSelection::LoadGame
+                        }
+                        MainMenuSelection::Quit => {
+                            newselection = MainMenuSelection::Seed { typed: false }
+                        }
                     }
                     if newselection == MainMenuSelection::LoadGame && !save_exists {
                         newselection = MainMenuSelection::NewGame;
@@ -584,11 +765,14 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                     let mut newselection;
                     match selection {
                         MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
+                        MainMenuSelection::LoadGame => {
+                            newselection = MainMenuSelection::Seed { typed: false }
+                        }
+                        MainMenuSelection::Seed { .. } => newselection = MainMenuSelection::Quit,
                         MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame,
                     }
                     if newselection == MainMenuSelection::LoadGame && !save_exists {
-                        newselection = MainMenuSelection::Quit;
+                        newselection = MainMenuSelection::Seed { typed: false };
                     }
                     return MainMenuResult::NoSelection {
                         selected: newselection,
@@ -600,9 +784,15 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                     }
                 }
                 _ => {
+                    if let MainMenuSelection::Seed { typed } = selection {
+                        let typed = edit_seed(&mut gs.ecs.fetch_mut::<MasterSeed>(), typed, key);
+                        return MainMenuResult::NoSelection {
+                            selected: MainMenuSelection::Seed { typed },
+                        };
+                    }
                     return MainMenuResult::NoSelection {
                         selected: selection,
-                    }
+                    };
                 }
             },
         }
@@ -613,13 +803,53 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     }
 }
 
+/// Applies a key pressed on the seed entry, returning whether the player is
+/// now typing a seed of their own.
+fn edit_seed(master_seed: &mut MasterSeed, typed: bool, key: VirtualKeyCode) -> bool {
+    let digit = match key {
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some(0),
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some(1),
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some(2),
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some(3),
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some(4),
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some(5),
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some(6),
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some(7),
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some(8),
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some(9),
+        _ => None,
+    };
+
+    match (key, digit) {
+        (VirtualKeyCode::R, _) => {
+            *master_seed = MasterSeed::random();
+            false
+        }
+        (VirtualKeyCode::Back, _) => {
+            master_seed.seed /= 10;
+            true
+        }
+        (_, Some(digit)) => {
+            let typed_so_far = if typed { master_seed.seed } else { 0 };
+            if let Some(seed) = typed_so_far
+                .checked_mul(10)
+                .and_then(|seed| seed.checked_add(digit))
+            {
+                master_seed.seed = seed;
+            }
+            true
+        }
+        _ => typed,
+    }
+}
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum GameOverResult {
     NoSelection,
     QuitToMenu,
 }
 
-pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
+pub fn game_over(ecs: &World, ctx: &mut Rltk) -> GameOverResult {
     ctx.cls();
 
     ctx.print_color_centered(
@@ -641,8 +871,17 @@ pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
         "That day, sadly, is not in this chapter..",
     );
 
+    let seed = ecs.fetch::<MasterSeed>().seed;
+    let depth = ecs.fetch::<Map>().depth;
+    ctx.print_color_centered(
+        22,
+        RGB::named(rltk::GREY),
+        RGB::named(rltk::BLACK),
+        format!("Seed {}, depth {}", seed, depth),
+    );
+
     ctx.print_color_centered(
-        20,
+        24,
         RGB::named(rltk::MAGENTA),
         RGB::named(rltk::BLACK),
         "Press any key to return to the menu.",
@@ -1,2 +1,3 @@
+pub mod camera;
 pub mod gamelog;
 pub mod gui;